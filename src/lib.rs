@@ -1,4 +1,10 @@
-// mod wordchain;
-mod wordchain_token;
-
+// Each contract is built into its own wasm; with both features on, their exported methods would clash.
+#[cfg(all(target_arch = "wasm32", feature = "token", feature = "tournament"))]
+compile_error!("Build the wasm with exactly one of the `token` and `tournament` features, e.g. --no-default-features --features token");
 
+#[cfg(any(feature = "token", feature = "tournament"))]
+pub mod upgrade;
+#[cfg(feature = "tournament")]
+pub mod wordchain;
+#[cfg(feature = "token")]
+pub mod wordchain_token;
@@ -0,0 +1,70 @@
+//! Staged code upgrades shared by both contracts. The wasm is kept under its own storage keys rather than
+//! in the contract struct, so staging code does not change the state layout or load the blob on every call.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::Serialize;
+use near_sdk::{env, require, Gas, Promise};
+
+const STAGED_CODE_KEY: &[u8] = b"STAGED_CODE";
+const STAGED_CODE_INFO_KEY: &[u8] = b"STAGED_CODE_INFO";
+
+/// Time between staging code and being allowed to deploy it, so players can review an upgrade before it lands.
+pub const UPGRADE_TIMELOCK_MS: u64 = 2 * 24 * 60 * 60 * 1000;
+pub const GAS_FOR_MIGRATE: Gas = Gas(50_000_000_000_000);
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StagedCode {
+    /// Hex encoded sha256 of the staged wasm.
+    pub sha256: String,
+    pub size: u64,
+    pub staged_at: u64,
+    pub deployable_at: u64,
+}
+
+/// Stores the raw call input as the next contract code, replacing anything staged before.
+pub(crate) fn stage_code() -> StagedCode {
+    let code = env::input().unwrap_or_default();
+    require!(!code.is_empty(), "Expected the contract code as input");
+
+    let now = env::block_timestamp_ms();
+    let staged = StagedCode {
+        sha256: hex(&env::sha256(&code)),
+        size: code.len() as u64,
+        staged_at: now,
+        deployable_at: now + UPGRADE_TIMELOCK_MS,
+    };
+    env::storage_write(STAGED_CODE_KEY, &code);
+    env::storage_write(STAGED_CODE_INFO_KEY, &staged.try_to_vec().unwrap());
+    staged
+}
+
+pub(crate) fn staged_code() -> Option<StagedCode> {
+    env::storage_read(STAGED_CODE_INFO_KEY).map(|bytes| StagedCode::try_from_slice(&bytes).unwrap())
+}
+
+pub(crate) fn cancel_staged_code() {
+    require!(staged_code().is_some(), "No code is staged");
+    env::storage_remove(STAGED_CODE_KEY);
+    env::storage_remove(STAGED_CODE_INFO_KEY);
+}
+
+/// Deploys the staged code once its timelock has passed and calls `migrate` on the new code in the same batch,
+/// so a failed migration rolls the deployment back.
+pub(crate) fn deploy_staged_code() -> Promise {
+    let staged = staged_code().unwrap_or_else(|| env::panic_str("No code is staged"));
+    require!(env::block_timestamp_ms() >= staged.deployable_at, "Staged code is still timelocked");
+
+    let code = env::storage_read(STAGED_CODE_KEY).unwrap();
+    require!(hex(&env::sha256(&code)) == staged.sha256, "Staged code does not match its hash");
+    env::storage_remove(STAGED_CODE_KEY);
+    env::storage_remove(STAGED_CODE_INFO_KEY);
+
+    Promise::new(env::current_account_id())
+        .deploy_contract(code)
+        .function_call("migrate".to_string(), Vec::new(), 0, GAS_FOR_MIGRATE)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -1,15 +1,67 @@
 
 use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, LookupMap, Vector};
-use near_sdk::json_types::U128;
-use near_sdk::serde::Serialize;
-use near_sdk::{env, log, near_bindgen, AccountId, Balance, PanicOnDefault, BorshStorageKey, PromiseOrValue, ext_contract, require};
+use near_sdk::collections::{LookupMap, UnorderedSet, Vector};
+use std::collections::HashMap;
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Balance, CryptoHash, Gas, PanicOnDefault, Promise, PromiseError, PromiseOrValue, ext_contract, require};
+
+mod bracket;
+mod challenge;
+mod commission;
+mod country;
+mod eligibility;
+mod events;
+mod free_entry;
+mod governance;
+mod lifecycle;
+mod migration;
+mod payout;
+mod profile;
+mod referral;
+mod schedule;
+mod settlement;
+mod solvency;
+mod sponsor;
+mod template;
+mod team;
+mod transfer;
+mod treasury;
+
+pub use bracket::{BracketMatch, BracketSeeding, TournamentFormat};
+pub use challenge::{Challenge, ChallengeEntry, ChallengeState};
+pub use eligibility::{EligibilityCheck, EligibilityRule};
+pub use commission::{CommissionSettings, DEFAULT_MAX_COMMISSION, DEFAULT_MIN_COMMISSION};
+pub use events::{WordchainEvent, EVENT_STANDARD, EVENT_VERSION};
+pub use free_entry::FREE_ENTRY_DEPOSIT;
+pub use governance::{AdminSettings, Proposal, ProposalAction, ProposalStatus, DEFAULT_PROPOSAL_DELAY_MS};
+pub use migration::{LegacyStake, CURRENT_STATE_VERSION, STATE_VERSION_KEY};
+pub use payout::DEFAULT_MAX_CREATOR_FEE;
+pub use profile::{PlayerProfile, RatingRange, DEFAULT_RATING};
+pub use referral::{ReferralStats, DEFAULT_REFERRAL_SHARE};
+pub use schedule::{RegistrationWindow, ScheduleInput, TimeInput};
+pub use settlement::{RankPayout, SettlementPreview};
+pub use solvency::{Liabilities, LiabilitiesReport, SolvencyReport};
+pub use sponsor::Sponsorship;
+pub use template::{Recurrence, TournamentConfig, TournamentSettings, TournamentTemplate, TEMPLATE_KEY_PREFIX};
+pub use team::{Team, TeamAggregation, TeamPrizeSplit, TeamSettings, TeamStanding};
+pub use transfer::{TransferFallback, GAS_FOR_FT_TRANSFER, GAS_FOR_RESOLVE_TRANSFER};
+pub use treasury::{TreasuryReport, TreasuryTotals, Withdrawal, WithdrawalLimit};
 
 pub const DAY_TO_MS: u64 = 86400000;
-
+pub const DEFAULT_REVEAL_WINDOW_MS: u64 = DAY_TO_MS;
+pub const DEFAULT_MAX_TOURNAMENT_PLAYERS: u32 = 1000;
+/// Basis points of the prize pool paid to first, second and third place.
+pub const DEFAULT_PRIZE_CURVE: [u64; 3] = [5000, 3400, 1600];
+/// Share of a settled tournament's commission, in basis points, paid to whoever advances it.
+pub const DEFAULT_KEEPER_BOUNTY: u64 = 500;
+pub const GAS_FOR_FT_BALANCE_OF: Gas = Gas(10_000_000_000_000);
+pub const GAS_FOR_RECONCILE_CALLBACK: Gas = Gas(10_000_000_000_000);
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
 #[derive(Eq, PartialEq)]
-#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub enum TournamentType {
     PUBLIC,
@@ -17,16 +69,20 @@ pub enum TournamentType {
     COUNTRY_BASED,
 }
 
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
 #[derive(Eq, PartialEq)]
 #[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
 #[serde(crate = "near_sdk::serde")]
 pub enum TournamentState {
     PENDING_VOLUME,
     ACTIVE,
+    REVEAL,
     DELETED,
-    CLOSED
+    CLOSED,
+    EXPIRED
 }
 
+#[allow(dead_code, clippy::upper_case_acronyms)]
 #[derive(Eq, PartialEq)]
 #[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
 #[serde(crate = "near_sdk::serde")]
@@ -37,26 +93,91 @@ enum PlayerState {
 
 #[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
 #[serde(crate = "near_sdk::serde")]
-struct GameType {
+pub struct GameType {
     identifier: String,
     max_score: u64,
 }
 
+#[derive(Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoundScore {
+    score: u64,
+    number_of_games_played: u64,
+}
+
+impl RoundScore {
+    /// Average score per game scaled by 1000, zero when no game was revealed.
+    fn average_score(&self) -> u64 {
+        if self.number_of_games_played == 0 {
+            return 0;
+        }
+        (self.score * 1000) / self.number_of_games_played
+    }
+}
+
 #[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
 #[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
 #[serde(crate = "near_sdk::serde")]
-struct TournamentPlayer {
+pub struct TournamentPlayer {
     id: U128,
     account_id: AccountId,
     stake_amount: Balance,
+    entry_deposit: Balance,
     score: u64,
     number_of_games_played: u64,
-    join_date: u64
+    join_date: u64,
+    round_scores: Vec<RoundScore>,
+    eliminated_in_round: Option<u32>,
+    #[serde(skip)]
+    score_commits: Vec<(u32, CryptoHash)>,
+}
+
+impl TournamentPlayer {
+    fn new(id: U128, account_id: AccountId, stake_amount: Balance) -> Self {
+        Self {
+            id,
+            account_id,
+            stake_amount,
+            entry_deposit: 0,
+            score: 0,
+            number_of_games_played: 0,
+            join_date: env::block_timestamp_ms(),
+            round_scores: Vec::new(),
+            eliminated_in_round: None,
+            score_commits: Vec::new(),
+        }
+    }
+
+    fn round_score(&self, round: u32) -> RoundScore {
+        self.round_scores.get(round as usize).cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TournamentRound {
+    round: u32,
+    start: u64,
+    end: u64,
+    reveal_deadline: u64,
+    eliminations: u32,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoundSchedule {
+    round: u32,
+    start: u64,
+    end: u64,
+    reveal_deadline: u64,
+    eliminations: u32,
+    challenge_seed: Option<Base64VecU8>,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize)]
 #[serde(crate = "near_sdk::serde")]
-struct Tournament {
+pub struct Tournament {
     id: U128,
     name: String,
     tournament_key: String,
@@ -64,35 +185,145 @@ struct Tournament {
     owner: AccountId,
     minimum_stake: Balance,
     created_at: u64,
+    registration: RegistrationWindow,
     total_stake: Balance,
     country: String,
     ft_address: AccountId,
     tournament_deadline: u64,
+    reveal_deadline: u64,
     tournament_type: TournamentType,
+    format: TournamentFormat,
+    bracket_seeding: BracketSeeding,
     status: TournamentState,
+    challenge_seed: Option<Base64VecU8>,
+    rounds: Vec<TournamentRound>,
+    rounds_settled: u32,
+    bracket: Vec<BracketMatch>,
+    team_settings: Option<TeamSettings>,
+    teams: Vec<Team>,
+    eligibility_rules: Vec<EligibilityRule>,
+    min_players: u32,
+    max_players: u32,
+    prize_curve: Vec<u64>,
+    /// Basis points of the pool taken as platform commission, fixed when the tournament is created.
+    commission: u64,
+    /// Basis points of the pool paid to the creator out of the platform commission.
+    creator_fee: u64,
+    sponsorships: Vec<Sponsorship>,
+    waitlist: Vec<TournamentPlayer>,
     players: Vec<TournamentPlayer>
 }
 
-#[ext_contract(ext_token_contract)]
-trait StableCoin {
-    fn ft_transfer(&self, to: &AccountId, amount: Balance, memo: Option<String>);
+impl Tournament {
+    fn round_at(&self, timestamp: u64) -> Option<&TournamentRound> {
+        self.rounds.iter().find(|round| round.start <= timestamp && timestamp < round.end)
+    }
+
+    fn round_in_reveal_at(&self, timestamp: u64) -> Option<&TournamentRound> {
+        self.rounds.iter().find(|round| round.end <= timestamp && timestamp < round.reveal_deadline)
+    }
+
+    fn next_player_id(&self) -> U128 {
+        U128(self.players.iter().chain(self.waitlist.iter()).map(|player| player.id.0).max().unwrap_or_default() + 1)
+    }
+
+    fn final_round(&self) -> u32 {
+        self.rounds.len() as u32 - 1
+    }
+
+    /// Eliminates the bottom players of every round whose reveal window has passed and was not settled yet.
+    fn settle_rounds(&mut self, timestamp: u64) {
+        while (self.rounds_settled as usize) < self.rounds.len() && self.rounds[self.rounds_settled as usize].reveal_deadline <= timestamp {
+            let round = self.rounds[self.rounds_settled as usize].clone();
+            let mut remaining = self.players.iter_mut().filter(|player| player.eliminated_in_round.is_none()).collect::<Vec<&mut TournamentPlayer>>();
+            let eliminations = (round.eliminations as usize).min(remaining.len().saturating_sub(1));
+
+            remaining.sort_by_key(|player| player.round_score(round.round).average_score());
+            remaining.into_iter().take(eliminations).for_each(|player| player.eliminated_in_round = Some(round.round));
 
+            self.rounds_settled += 1;
+        }
+    }
+
+    /// Players ranked by their final-round average, followed by eliminated players, latest elimination first
+    /// and then by the average of the round they were eliminated in.
+    fn standings(&self) -> Vec<TournamentPlayer> {
+        let final_round = self.final_round();
+        let mut players = self.players.clone();
+
+        players.sort_by_key(|player| std::cmp::Reverse((
+            player.eliminated_in_round.unwrap_or(u32::MAX),
+            player.round_score(player.eliminated_in_round.unwrap_or(final_round)).average_score(),
+        )));
+        players
+    }
+
+    /// Accounts grouped by prize placement with the weight each one gets of the placement prize.
+    fn placements(&self) -> Vec<Vec<(AccountId, Balance)>> {
+        if self.team_settings.is_some() {
+            return self.team_placements();
+        }
+
+        match self.format {
+            TournamentFormat::LEADERBOARD => self.standings().into_iter().map(|player| vec![(player.account_id, 1)]).collect(),
+            TournamentFormat::BRACKET => self.bracket_placements().into_iter()
+                .map(|accounts| accounts.into_iter().map(|account_id| (account_id, 1)).collect())
+                .collect(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+/// The NEP-141 methods called on the tokens tournaments are played for.
+#[ext_contract(ext_token_contract)]
+trait FungibleTokenContract {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+    fn ft_balance_of(&self, account_id: AccountId) -> U128;
 }
 
 #[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize)]
-struct Wordchain {
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Wordchain {
     paused: bool,
     owner: AccountId,
     pending_owner: AccountId,
+    admins: Vec<AccountId>,
+    admin_threshold: u32,
+    proposal_delay: u64,
+    proposals: Vector<Proposal>,
     min_tournament_players: u8,
+    max_tournament_players: u32,
     stake_payouts: LookupMap<AccountId, Balance>,
+    treasury_totals: LookupMap<AccountId, TreasuryTotals>,
+    withdrawals: Vector<Withdrawal>,
+    withdrawal_limits: LookupMap<AccountId, WithdrawalLimit>,
+    withdrawal_allowlist: Vec<AccountId>,
+    liabilities: LookupMap<AccountId, Liabilities>,
     percentage_stake_commission: u64,
+    min_commission: u64,
+    max_commission: u64,
+    token_commissions: LookupMap<AccountId, u64>,
+    game_type_commissions: LookupMap<String, u64>,
+    reveal_window: u64,
+    keeper_bounty: u64,
+    sponsorship_commission: bool,
+    max_creator_fee: u64,
+    creator_earnings: LookupMap<AccountId, HashMap<AccountId, Balance>>,
+    referral_share: u64,
+    referrers: LookupMap<AccountId, AccountId>,
+    referral_stats: LookupMap<AccountId, ReferralStats>,
     game_types: Vector<GameType>,
     tournaments_to_players: LookupMap<U128, Vector<AccountId>>,
     tournaments: Vector<Tournament>,
-    supported_countries: Vector<String>,
+    supported_countries: UnorderedSet<String>,
+    country_tournaments: LookupMap<String, Vec<U128>>,
     stakes: LookupMap<AccountId, LookupMap<AccountId, Balance>>,
+    /// Set by the V1 migration until the owner has imported the balances staked before it.
+    legacy_stake_import: bool,
+    challenges: Vector<Challenge>,
+    player_profiles: LookupMap<AccountId, PlayerProfile>,
+    wct_token: Option<AccountId>,
+    templates: Vector<TournamentTemplate>,
 
 }
 
@@ -100,259 +331,284 @@ struct Wordchain {
 impl Wordchain {
 
     #[init]
-    pub fn new(percentage_stake_commission: u64, countries: String, min_tournament_players: u8) -> Self {
+    pub fn new(percentage_stake_commission: u64, countries: Vec<String>, min_tournament_players: u8) -> Self {
         require!(min_tournament_players > 3, "Minimum number of players must be greater then 3");
-        require!(percentage_stake_commission > 1000, "Commission must be at least 10 percent");
-        require!(countries.replace("|", "").len() > 2, "Enter at least one valid country code.");
-        let countries_split = countries.split("|").collect::<Vec<&str>>();
-
-        let mut supported_countries = Vector::new(b"c");
-
-        countries_split.into_iter().for_each(|cont| supported_countries.push(&cont.to_string()));
-
-        Self {
+        require!(
+            (DEFAULT_MIN_COMMISSION..=DEFAULT_MAX_COMMISSION).contains(&percentage_stake_commission),
+            format!("Commission must be between {} and {} basis points", DEFAULT_MIN_COMMISSION, DEFAULT_MAX_COMMISSION)
+        );
+        require!(!countries.is_empty(), "Enter at least one valid country code.");
+        migration::VersionedWordchain::write_version();
+
+        let mut contract = Self {
             paused: bool::default(),
             owner: env::signer_account_id(),
             pending_owner: env::current_account_id(),
+            admins: Vec::new(),
+            admin_threshold: 0,
+            proposal_delay: DEFAULT_PROPOSAL_DELAY_MS,
+            proposals: Vector::new(b"a"),
             min_tournament_players,
+            max_tournament_players: DEFAULT_MAX_TOURNAMENT_PLAYERS,
             stake_payouts: LookupMap::new(b"d"),
+            treasury_totals: LookupMap::new(b"x"),
+            withdrawals: Vector::new(b"h"),
+            withdrawal_limits: LookupMap::new(b"l"),
+            withdrawal_allowlist: Vec::new(),
+            liabilities: LookupMap::new(b"y"),
             percentage_stake_commission,
+            min_commission: DEFAULT_MIN_COMMISSION,
+            max_commission: DEFAULT_MAX_COMMISSION,
+            token_commissions: LookupMap::new(b"k"),
+            game_type_commissions: LookupMap::new(b"n"),
+            reveal_window: DEFAULT_REVEAL_WINDOW_MS,
+            keeper_bounty: DEFAULT_KEEPER_BOUNTY,
+            sponsorship_commission: false,
+            max_creator_fee: DEFAULT_MAX_CREATOR_FEE,
+            creator_earnings: LookupMap::new(b"e"),
+            referral_share: DEFAULT_REFERRAL_SHARE,
+            referrers: LookupMap::new(b"f"),
+            referral_stats: LookupMap::new(b"q"),
             game_types: Vector::new(b"g"),
             tournaments_to_players: LookupMap::new(b"p"),
             tournaments: Vector::new(b"t"),
-            supported_countries,
-            stakes: LookupMap::new(b"s")
-        }
+            supported_countries: UnorderedSet::new(b"u"),
+            country_tournaments: LookupMap::new(b"i"),
+            stakes: LookupMap::new(b"s"),
+            legacy_stake_import: false,
+            challenges: Vector::new(b"w"),
+            player_profiles: LookupMap::new(b"r"),
+            wct_token: None,
+            templates: Vector::new(b"m"),
+        };
+        contract.internal_add_supported_countries(countries);
+        contract
     }
 
-    /// .
-    pub fn create_tournament(&mut self, 
-        name: String, 
+    /// Creates a tournament played for `minimum_stake` of `ft_address`; `settings` left out take the platform defaults.
+    /// A creator other than the contract owner enters it, attaching `FREE_ENTRY_DEPOSIT` for a free tournament.
+    #[payable]
+    pub fn create_tournament(&mut self,
+        name: String,
         tournament_key: String,
-        game_type_id: String, 
-        form: TournamentType, 
-        interval: u64, 
-        minimum_stake: U128, 
+        game_type_id: String,
         ft_address: AccountId,
-        country: Option<String>) -> Tournament {
-
-        match form {
-            TournamentType::COUNTRY_BASED => {
-                require!(country.is_some(), "Country based tournament requires a country to be passed");
-                require!(self.check_supported_country(country.clone().unwrap_or_default()), "Country code entered is not supported");
-            },
-            _ => {}
-        }
+        minimum_stake: U128,
+        settings: Option<TournamentSettings>) -> Tournament {
 
+        require!(!self.paused, "Contract is paused");
+        self.internal_assert_not_template_instance(&name, &tournament_key);
         require!(self.get_tournament_by_key_or_name(tournament_key.clone(), name.clone()).is_none(), "Tournament with provided key or name already exists");
-        require!(self.get_gametypes(Some(game_type_id.clone())).len() > 0, "No tournament with provided game type");
 
-        if let Some(stakings) = Some(self.stakes.get(&env::predecessor_account_id())) {
-            let mut stakes = stakings.unwrap();
-            let ft_stake = stakes.get(&ft_address).unwrap_or_else(|| env::panic_str("No stake made"));
+        let mut settings = settings.unwrap_or_default();
+        let schedule = settings.schedule.take();
+        let config = settings.into_config(self, game_type_id, ft_address, minimum_stake);
+        config.assert_valid(self);
 
-            if ft_stake < minimum_stake.0 {
-                env::panic_str("You must have staked at least the minimum stake before creating tournament");
-            } else {
-
-                let tournament_id = U128::from((self.tournaments.len() as u128) + 1);
-
-                let mut players_ = Vec::new();
-
-                if env::predecessor_account_id() != self.owner {
-                    players_.push(TournamentPlayer {
-                        id: U128::from(1),
-                        account_id: env::predecessor_account_id(),
-                        stake_amount: minimum_stake.0,
-                        score: 0,
-                        number_of_games_played: 0,
-                        join_date: env::block_timestamp_ms(),
-                    });
-                }
-
-                let tournament = Tournament {
-                    id: tournament_id,
-                    name,
-                    tournament_key,
-                    game_type_id,
-                    owner: env::predecessor_account_id(),
-                    minimum_stake: minimum_stake.0,
-                    total_stake: 0,
-                    country: country.unwrap_or_default(),
-                    ft_address,
-                    created_at: env::block_timestamp_ms(),
-                    tournament_deadline: env::block_timestamp_ms() + (interval * DAY_TO_MS),
-                    tournament_type: form,
-                    status: TournamentState::PENDING_VOLUME,
-                    players: players_
-                };
-
-                self.tournaments.push(&tournament);
-
-                stakes.insert(&tournament.ft_address, &0);
-                self.stakes.insert(&env::predecessor_account_id(), &stakes);
-
-                tournament
-            }
+        // A creator other than the contract owner enters with the minimum stake; the rest of their deposit stays theirs.
+        let creator = env::predecessor_account_id();
+        let mut players_ = Vec::new();
+        if creator == self.owner {
+            require!(env::attached_deposit() == 0, "This tournament does not take a deposit");
         } else {
-            env::panic_str("No stake made");
+            let entry_deposit = self.internal_take_entry_deposit(config.minimum_stake.0, &config.eligibility_rules, &creator);
+            if config.minimum_stake.0 > 0 {
+                let ft_stake = self.stakes.get(&creator).and_then(|stakes| stakes.get(&config.ft_address));
+                require!(ft_stake.is_some(), "No stake made");
+                require!(ft_stake.unwrap() >= config.minimum_stake.0, "You must have staked at least the minimum stake before creating tournament");
+
+                self.internal_debit_stake(&creator, &config.ft_address, config.minimum_stake.0);
+                self.internal_lock_pool(&config.ft_address, config.minimum_stake.0);
+            }
+            let mut player = TournamentPlayer::new(U128::from(1), creator.clone(), config.minimum_stake.0);
+            player.entry_deposit = entry_deposit;
+            players_.push(player);
         }
 
+        self.internal_create_tournament(name, tournament_key, creator, config, schedule.unwrap_or_default(), players_)
     }
 
 
-    /// .
+    /// Joins with the caller's staked balance, or with `FREE_ENTRY_DEPOSIT` attached for a free tournament.
+    /// A `referrer` is only recorded the first time the caller is referred.
+    #[payable]
     pub fn join_tournament(&mut self,
         tournament_id: U128,
         country: String,
         tournament_key: Option<String>,
+        referrer: Option<AccountId>,
     ) {
-        let mut tournament = self.tournaments.get(tournament_id.0 as u64).unwrap_or_else(|| env::panic_str("Tournament with provided ID does not exist"));
-        require!(tournament.owner != env::predecessor_account_id(), "Tournament owner can not join the tournament");
-        require!(tournament.tournament_deadline > env::block_timestamp_ms(), "Tournament exceeded the deadline");
+        require!(!self.paused, "Contract is paused");
+        let mut tournament = self.internal_get_tournament(tournament_id);
+        let failures = self.internal_eligibility_failures(&tournament, &env::predecessor_account_id());
+        require!(failures.is_empty(), failures.join("; "));
 
         match tournament.tournament_type {
             TournamentType::PRIVATE => {
                 require!(tournament.tournament_key == tournament_key.unwrap_or_default(), "Invalid tournament key provided for a private tournament");
             },
             TournamentType::COUNTRY_BASED => {
-                require!(tournament.country == country, "Invalid country");
+                require!(country::normalize_country(&country).as_ref() == Some(&tournament.country), "Invalid country");
             },
             _ => {}
         }
 
-        if let Some(stakings) = Some(self.stakes.get(&env::predecessor_account_id())) {
+        let account_id = env::predecessor_account_id();
+        self.internal_record_referrer(&account_id, referrer);
+        let entry_deposit = self.internal_take_entry_deposit(tournament.minimum_stake, &tournament.eligibility_rules, &account_id);
+        let ft_stake = self.internal_take_entry_stake(&tournament, &account_id);
+        self.internal_lock_pool(&tournament.ft_address, ft_stake);
 
-            let mut stakes = stakings.unwrap();
-            let ft_stake = stakes.get(&tournament.ft_address).unwrap_or_else(|| env::panic_str("No stake made"));
+        let mut player = TournamentPlayer::new(tournament.next_player_id(), account_id.clone(), ft_stake);
+        player.entry_deposit = entry_deposit;
 
-            if ft_stake < tournament.minimum_stake {
-                env::panic_str("You must have staked at least the minimum stake before creating tournament");
-            } else {
-                let number_of_players = (tournament.players.len() as u128) + 1;
+        // A full tournament keeps the stake in escrow on its waitlist until a seat frees up.
+        let waitlisted = tournament.players.len() as u32 >= tournament.max_players;
+        WordchainEvent::PlayerJoined { tournament_id: tournament.id, account_id, stake: U128(ft_stake), waitlisted }.emit();
 
-                let player_id = U128::from(number_of_players.clone());
+        if waitlisted {
+            tournament.waitlist.push(player);
+        } else {
+            tournament.players.push(player);
+            tournament.total_stake += ft_stake;
+            self.internal_check_activation(&mut tournament);
+        }
 
-                tournament.players.push(TournamentPlayer {
-                    id: player_id,
-                    account_id: env::predecessor_account_id(),
-                    stake_amount: ft_stake,
-                    score: 0,
-                    number_of_games_played: 0,
-                    join_date: env::block_timestamp_ms(),
-                });
+        self.internal_save_tournament(&tournament);
+    }
 
-                if number_of_players >= self.min_tournament_players as u128 {
-                    tournament.status = TournamentState::ACTIVE;
-                }
 
-                self.tournaments.replace(tournament_id.0 as u64, &tournament);
-                stakes.insert(&tournament.ft_address, &0);
-                self.stakes.insert(&env::predecessor_account_id(), &stakes);
-            }
+    /// Withdraws the caller from the waitlist at any time, or from the players before the tournament is activated,
+    /// refunding the full stake. A freed seat goes to the first account on the waitlist.
+    pub fn leave_tournament(&mut self, tournament_id: U128) {
+        let mut tournament = self.internal_get_tournament(tournament_id);
+        let account_id = env::predecessor_account_id();
 
-        } else {
-            env::panic_str("No stake made");
-        }
-  
-        
+        let player = match tournament.waitlist.iter().position(|player| player.account_id == account_id) {
+            Some(position) => tournament.waitlist.remove(position),
+            None => {
+                let position = tournament.players.iter().position(|player| player.account_id == account_id);
+                require!(position.is_some(), "Player is not part of the tournament");
+                require!(tournament.status == TournamentState::PENDING_VOLUME, "Players can only leave before the tournament is activated");
+
+                let player = tournament.players.remove(position.unwrap());
+                tournament.total_stake -= player.stake_amount;
+                tournament.remove_from_team(&account_id);
+
+                if !tournament.waitlist.is_empty() {
+                    let promoted = tournament.waitlist.remove(0);
+                    tournament.total_stake += promoted.stake_amount;
+                    tournament.players.push(promoted);
+                    self.internal_check_activation(&mut tournament);
+                }
+                player
+            },
+        };
+
+        self.internal_refund_entry(&tournament, &player);
+        self.internal_save_tournament(&tournament);
     }
 
+    pub fn get_waitlist(&self, tournament_id: U128) -> Vec<AccountId> {
+        self.internal_get_tournament(tournament_id).waitlist.into_iter().map(|player| player.account_id).collect()
+    }
 
-    #[doc = r"Function to handle score update after playing a game"]
-    pub fn publish_score(&mut self, tournament_id: U128, score: u8) {
+    #[doc = r"Function to commit to a score after playing a game, revealed once the tournament deadline passes"]
+    pub fn commit_score(&mut self, tournament_id: U128, score_hash: Base64VecU8) {
 
-        let mut tournament = self.tournaments.get(tournament_id.0 as u64).unwrap_or_else(|| env::panic_str("Tournament with provided ID does not exist"));
+        require!(!self.paused, "Contract is paused");
+        let mut tournament = self.internal_get_tournament(tournament_id);
         require!(tournament.tournament_deadline > env::block_timestamp_ms(), "Tournament exceeded deadline");
-        require!(tournament.status == TournamentState::ACTIVE, "Tournament can not be joined at this time");
+        self.internal_check_activation(&mut tournament);
+        require!(tournament.status == TournamentState::ACTIVE, "Tournament can not be played at this time");
+        tournament.settle_rounds(env::block_timestamp_ms());
 
-        let gametype = self.get_gametypes(Some(tournament.game_type_id.clone()));
-        require!(score as u64 <= gametype[0].max_score, "Score exceeds threshold for game");
+        let round = tournament.round_at(env::block_timestamp_ms()).map(|round| round.round);
+        require!(round.is_some(), "No round is being played at this time");
 
-        tournament.players.clone().into_iter().for_each(|mut player| {
-            if player.account_id == env::predecessor_account_id() {
-                player.score += score as u64;
-                player.number_of_games_played += 1;
-            }
-        });
+        let score_hash: CryptoHash = score_hash.0.try_into().unwrap_or_else(|_| env::panic_str("Score hash must be 32 bytes"));
+        tournament.assert_can_play_for_team(&env::predecessor_account_id());
+
+        let player = tournament.players.iter_mut()
+            .find(|player| player.account_id == env::predecessor_account_id())
+            .unwrap_or_else(|| env::panic_str("Only tournament players can commit scores"));
+        require!(player.eliminated_in_round.is_none(), "Player was eliminated from the tournament");
 
+        // One commit per round, so a player can not commit several scores and reveal only the best.
+        let round = round.unwrap();
+        player.score_commits.retain(|(committed_round, _)| *committed_round != round);
+        player.score_commits.push((round, score_hash));
 
-        self.tournaments.replace(tournament_id.0 as u64, &tournament);
+        self.internal_save_tournament(&tournament);
 
     }
 
+    #[doc = r"Function to reveal a committed score during the reveal window"]
+    pub fn reveal_score(&mut self, tournament_id: U128, score: u8, salt: String) {
 
-    //Admin level
-    pub fn distribute_rewards(&mut self, tournament_id: U128) {
+        let mut tournament = self.internal_get_tournament(tournament_id);
+        require!(tournament.reveal_deadline > env::block_timestamp_ms(), "Tournament exceeded reveal deadline");
+        require!(
+            tournament.status == TournamentState::ACTIVE || tournament.status == TournamentState::REVEAL,
+            "Tournament scores can not be revealed at this time"
+        );
 
-        let mut tournament = self.tournaments.get(tournament_id.0 as u64).unwrap_or_else(|| env::panic_str("Tournament with provided ID does not exist"));
-        require!(self.owner == env::predecessor_account_id(), "Unauthorized");
-        require!(tournament.tournament_deadline <= env::block_timestamp_ms(), "Tournament owner can not join the tournament");
-        require!(tournament.status != TournamentState::CLOSED, "Tournament is already closed");
+        let round = tournament.round_in_reveal_at(env::block_timestamp_ms()).map(|round| round.round);
+        require!(round.is_some(), "Scores can only be revealed after the round ends");
+        let round = round.unwrap();
+
+        let gametype = self.get_gametypes(Some(tournament.game_type_id.clone()));
+        require!(score as u64 <= gametype[0].max_score, "Score exceeds threshold for game");
 
-        let mut stake_payout_for_ft = self.stake_payouts.get(&tournament.ft_address).unwrap_or_default();
+        let score_hash = Self::internal_hash_score(&env::predecessor_account_id(), score, &salt);
 
-        match tournament.status {
-            TournamentState::PENDING_VOLUME => {
-                tournament.players.iter().for_each(|player| {
-                    
-                    if player.account_id != tournament.owner {
-                        ext_token_contract::ext(tournament.ft_address.clone())
-                        .ft_transfer(
-                            &player.account_id,
-                            player.stake_amount,
-                            None
-                        );
-
-                    } else {
-
-                        stake_payout_for_ft += player.stake_amount;
-                        self.stake_payouts.insert(&tournament.ft_address, &stake_payout_for_ft);
-                    }
-                });
+        let player = tournament.players.iter_mut()
+            .find(|player| player.account_id == env::predecessor_account_id())
+            .unwrap_or_else(|| env::panic_str("Only tournament players can reveal scores"));
+        let position = player.score_commits.iter().position(|commit| *commit == (round, score_hash));
+        require!(position.is_some(), "No commit matches the revealed score");
 
-                tournament.status = TournamentState::CLOSED;
-                self.tournaments.replace(tournament_id.0 as u64, &tournament);
-            },
-            TournamentState::DELETED => {},
-            _ => {
-                let commission = (self.percentage_stake_commission as u128 * tournament.total_stake) / 10000;
-                stake_payout_for_ft += commission;
-                self.stake_payouts.insert(&tournament.ft_address, &stake_payout_for_ft);
+        player.score_commits.remove(position.unwrap());
+        player.score += score as u64;
+        player.number_of_games_played += 1;
+
+        if player.round_scores.len() <= round as usize {
+            player.round_scores.resize(round as usize + 1, RoundScore::default());
+        }
+        player.round_scores[round as usize].score += score as u64;
+        player.round_scores[round as usize].number_of_games_played += 1;
+
+        WordchainEvent::ScorePublished { tournament_id, account_id: env::predecessor_account_id(), round, score }.emit();
 
-                let pay_value = tournament.total_stake - commission;
+        if round == tournament.final_round() {
+            tournament.status = TournamentState::REVEAL;
+        }
+        self.internal_save_tournament(&tournament);
 
-                let mut players = tournament.players.clone().into_iter().map(|player| player).collect::<Vec<TournamentPlayer>>();
+    }
 
-                players.sort_by(|a, b| ((b.score*1000)/b.number_of_games_played).cmp(&((a.score*1000)/a.number_of_games_played)));
 
-                let prizes = self.get_position_prizes();
+    //Admin level
+    pub fn distribute_rewards(&mut self, tournament_id: U128) {
 
-                for i in 1..prizes.len() {
-                    let val_to_pay = prizes[i] as u128 * pay_value;
+        let mut tournament = self.internal_get_tournament(tournament_id);
+        require!(self.owner == env::predecessor_account_id(), "Unauthorized");
+        self.internal_assert_closable(&tournament);
 
-                    ext_token_contract::ext(tournament.ft_address.clone())
-                        .ft_transfer(
-                            &players[i].account_id,
-                            val_to_pay,
-                            None
-                        );
-                }
-                tournament.status = TournamentState::CLOSED;
-                self.tournaments.replace(tournament_id.0 as u64, &tournament);
-            }
-        }
+        self.internal_close_tournament(&mut tournament);
+        self.internal_save_tournament(&tournament);
 
     }
 
 
     pub fn transfer_ownership(&mut self, new_owner: AccountId) {
-        require!(self.owner == env::predecessor_account_id(), "Unauthorized");
-        self.pending_owner = new_owner;
+        self.internal_assert_owner_action();
+        self.internal_transfer_ownership(new_owner);
     }
 
     pub fn accept_ownership(&mut self) {
         require!(self.pending_owner == env::predecessor_account_id(), "Unauthorized");
+        WordchainEvent::OwnershipTransferred { previous_owner: self.owner.clone(), owner: env::predecessor_account_id() }.emit();
         self.owner = env::predecessor_account_id();
         self.pending_owner = env::current_account_id();
     }
@@ -361,79 +617,53 @@ impl Wordchain {
         require!(self.owner == env::predecessor_account_id(), "Unauthorized");
         require!(!self.paused, "Contract is paused");
         self.paused = true;
+        WordchainEvent::config_changed("paused", true);
     }
 
     pub fn unpause_contract(&mut self) {
         require!(self.owner == env::predecessor_account_id(), "Unauthorized");
         require!(self.paused, "Contract is not paused");
         self.paused = false;
+        WordchainEvent::config_changed("paused", false);
     }
 
-    pub fn withdraw_value(&mut self, to: AccountId, ft_address: AccountId, amount: Option<U128>) -> Balance {
-
-        require!(self.owner == env::predecessor_account_id(), "Unauthorized");
-        let payout = self.stake_payouts.get(&ft_address).unwrap_or_default();
-        match amount {
-            Some(amt) => {
-                let mut bal: u128 = 0;
-                
-                if payout < amt.0 {
-                    env::panic_str("Confirm correct token address or withdraw amount below threshold");
-                } else {
-                    ext_token_contract::ext(ft_address.clone())
-                        .ft_transfer(
-                            &to,
-                            amt.0,
-                            None
-                        );
-                    
-                    bal = payout - amt.0
-                }
-                bal
-
-            },
-            None => {
-                ext_token_contract::ext(ft_address.clone())
-                    .ft_transfer(
-                        &to,
-                        payout,
-                        None
-                    );
-
-                0
-            }
-        }
+    pub fn set_min_players(&mut self, num: u8) {
+        self.internal_assert_owner_action();
+        self.internal_set_min_players(num);
     }
 
-    pub fn add_supported_country(&mut self, countries: String) {
+    pub fn set_max_players(&mut self, num: u32) {
         require!(self.owner == env::predecessor_account_id(), "Unauthorized");
-        let countries_split = countries.split("|");
+        require!(num >= self.min_tournament_players as u32, "Maximum number of players can not be below the minimum");
+        self.max_tournament_players = num;
+        WordchainEvent::config_changed("max_tournament_players", num);
+    }
 
-        for code in countries_split {
-            self.supported_countries.push(&code.to_string());
-        }
+    pub fn set_percentage_stake_commission(&mut self, new_value: u64) {
+        self.internal_assert_owner_action();
+        self.internal_set_percentage_stake_commission(new_value);
     }
 
-    pub fn set_min_players(&mut self, num: u8) {
+    pub fn set_reveal_window(&mut self, reveal_window_ms: u64) {
         require!(self.owner == env::predecessor_account_id(), "Unauthorized");
-        require!(num > 3, "Minimum number of players must be greater then 3");
-        self.min_tournament_players = num;
+        require!(reveal_window_ms > 0, "Reveal window must be greater than 0");
+        self.reveal_window = reveal_window_ms;
+        WordchainEvent::config_changed("reveal_window", reveal_window_ms);
     }
 
-    pub fn set_percentage_stake_commission(&mut self, new_value: u64) {
+    pub fn add_game_type(&mut self, identifier: String, max_score: u64) {
         require!(self.owner == env::predecessor_account_id(), "Unauthorized");
-        require!(new_value >= 1000, "Commission must be at least 10 percent");
-        self.percentage_stake_commission = new_value;
+        require!(self.get_gametypes(Some(identifier.clone())).is_empty(), "Game type with provided identifier already exists");
+        WordchainEvent::config_changed("game_type", format!("{}:{}", identifier, max_score));
+        self.game_types.push(&GameType { identifier, max_score });
     }
 
     // getters
     pub fn get_gametypes(&self, identifier: Option<String>) -> Vec<GameType> {
 
-        if let Some(id) = Some(identifier) {
-            let p = id.unwrap();
-            return self.game_types.iter().filter(|game| game.identifier == p).collect::<Vec<GameType>>();
-        } else {
-            return self.game_types.iter().map(|game| game).collect::<Vec<GameType>>();
+        match identifier {
+            Some(id) => self.game_types.iter().filter(|game| game.identifier == id).collect::<Vec<GameType>>(),
+            None => self.game_types.iter().collect::<Vec<GameType>>(),
         }
 
     }
@@ -452,55 +682,637 @@ impl Wordchain {
 
     pub fn get_all_tournaments(&self, owner: Option<AccountId>) -> Vec<Tournament> {
         
-        if let Some(owner) = Some(owner) {
-            let p = owner.unwrap();
-            return self.tournaments.iter().filter(|tournament| tournament.owner == p).collect::<Vec<Tournament>>();
+        match owner {
+            Some(owner) => self.tournaments.iter().filter(|tournament| tournament.owner == owner).collect::<Vec<Tournament>>(),
+            None => self.tournaments.iter().collect::<Vec<Tournament>>(),
+        }
+    }
+
+    pub fn get_position_prizes(&self) -> Vec<u64> {
+        Self::internal_position_prizes(&DEFAULT_PRIZE_CURVE, self.percentage_stake_commission)
+    }
+
+    /// Commitment a player submits through `commit_score`: sha256 of `"{account_id}:{score}:{salt}"`.
+    pub fn hash_score(&self, account_id: AccountId, score: u8, salt: String) -> Base64VecU8 {
+        Base64VecU8::from(Self::internal_hash_score(&account_id, score, &salt).to_vec())
+    }
+
+    pub fn get_round_schedule(&self, tournament_id: U128) -> Vec<RoundSchedule> {
+        let tournament = self.internal_get_tournament(tournament_id);
+
+        tournament.rounds.iter().map(|round| RoundSchedule {
+            round: round.round,
+            start: round.start,
+            end: round.end,
+            reveal_deadline: round.reveal_deadline,
+            eliminations: round.eliminations,
+            challenge_seed: tournament.challenge_seed.as_ref().map(|_| self.get_challenge_seed(tournament_id, round.round)),
+        }).collect()
+    }
+
+    /// Seed every player of the round builds the same puzzle from: sha256 of the tournament seed followed by the little-endian round.
+    pub fn get_challenge_seed(&self, tournament_id: U128, round: u32) -> Base64VecU8 {
+        let tournament = self.internal_get_tournament(tournament_id);
+        let seed = tournament.challenge_seed.unwrap_or_else(|| env::panic_str("Tournament has not been activated yet"));
+
+        Base64VecU8::from(env::sha256([seed.0, round.to_le_bytes().to_vec()].concat().as_slice()))
+    }
+    
+}
+
+impl Wordchain {
+
+    fn internal_get_tournament(&self, tournament_id: U128) -> Tournament {
+        tournament_id.0.checked_sub(1)
+            .and_then(|index| self.tournaments.get(index as u64))
+            .unwrap_or_else(|| env::panic_str("Tournament with provided ID does not exist"))
+    }
+
+    fn internal_save_tournament(&mut self, tournament: &Tournament) {
+        self.tournaments.replace((tournament.id.0 - 1) as u64, tournament);
+    }
+
+    /// Builds and stores a tournament from a validated config; the creator's players are already staked.
+    fn internal_create_tournament(
+        &mut self,
+        name: String,
+        tournament_key: String,
+        owner: AccountId,
+        config: TournamentConfig,
+        schedule: ScheduleInput,
+        players: Vec<TournamentPlayer>,
+    ) -> Tournament {
+        let (registration, schedule) = self.internal_tournament_schedule(
+            schedule,
+            config.round_duration,
+            config.rounds,
+            config.eliminations_per_round,
+        );
+        let final_round = schedule.last().unwrap();
+        let (tournament_deadline, reveal_deadline) = (final_round.end, final_round.reveal_deadline);
+
+        let commission = self.internal_commission_rate(&config.ft_address, &config.game_type_id);
+        let tournament = Tournament {
+            id: U128::from((self.tournaments.len() as u128) + 1),
+            name,
+            tournament_key,
+            game_type_id: config.game_type_id,
+            owner,
+            minimum_stake: config.minimum_stake.0,
+            total_stake: players.iter().map(|player| player.stake_amount).sum(),
+            country: config.country.map(|country| country::normalize_country(&country).unwrap_or(country)).unwrap_or_default(),
+            ft_address: config.ft_address,
+            created_at: env::block_timestamp_ms(),
+            registration,
+            tournament_deadline,
+            reveal_deadline,
+            tournament_type: config.form,
+            format: config.format,
+            bracket_seeding: config.bracket_seeding,
+            status: TournamentState::PENDING_VOLUME,
+            challenge_seed: None,
+            rounds: schedule,
+            rounds_settled: 0,
+            bracket: Vec::new(),
+            team_settings: config.team_settings,
+            teams: Vec::new(),
+            eligibility_rules: config.eligibility_rules,
+            min_players: config.min_players,
+            max_players: config.max_players,
+            prize_curve: config.prize_curve,
+            commission,
+            creator_fee: config.creator_fee,
+            sponsorships: Vec::new(),
+            waitlist: Vec::new(),
+            players,
+        };
+
+        self.tournaments.push(&tournament);
+        self.internal_index_country_tournament(&tournament);
+        WordchainEvent::TournamentCreated {
+            tournament_id: tournament.id,
+            name: tournament.name.clone(),
+            owner: tournament.owner.clone(),
+            ft_address: tournament.ft_address.clone(),
+            minimum_stake: U128(tournament.minimum_stake),
+        }.emit();
+        tournament
+    }
+
+    /// Back to back rounds of `round_duration`, each followed by its reveal window; the final round eliminates nobody.
+    fn internal_round_schedule(&self, start: u64, round_duration: u64, rounds: u32, eliminations_per_round: u32) -> Vec<TournamentRound> {
+        let mut schedule = Vec::with_capacity(rounds as usize);
+        let mut round_start = start;
+
+        for round in 0..rounds {
+            let end = round_start.checked_add(round_duration);
+            let reveal_deadline = end.and_then(|end| end.checked_add(self.reveal_window));
+            require!(reveal_deadline.is_some(), "Tournament schedule is too long");
+            let (end, reveal_deadline) = (end.unwrap(), reveal_deadline.unwrap());
+
+            schedule.push(TournamentRound {
+                round,
+                start: round_start,
+                end,
+                reveal_deadline,
+                eliminations: if round + 1 == rounds { 0 } else { eliminations_per_round },
+            });
+            round_start = reveal_deadline;
+        }
+        schedule
+    }
+
+    fn internal_assert_closable(&self, tournament: &Tournament) {
+        require!(
+            tournament.status != TournamentState::CLOSED && tournament.status != TournamentState::EXPIRED,
+            "Tournament is already closed"
+        );
+        if tournament.format == TournamentFormat::BRACKET && tournament.status == TournamentState::ACTIVE {
+            require!(tournament.bracket_winner().is_some(), "Bracket has not been decided yet");
         } else {
-            return self.tournaments.iter().map(|tournament| tournament).collect::<Vec<Tournament>>();
+            require!(tournament.tournament_deadline <= env::block_timestamp_ms(), "Tournament has not reached the deadline");
+            if tournament.status != TournamentState::PENDING_VOLUME {
+                require!(tournament.reveal_deadline <= env::block_timestamp_ms(), "Tournament is still in the reveal window");
+            }
         }
     }
 
-    pub fn check_supported_country(&self, country_code: String) -> bool {
-        for country in self.supported_countries.iter() {
-            if country == country_code {
-                return true
+    /// Refunds a tournament that never reached its minimum or pays out its prizes, returning the commission taken.
+    fn internal_close_tournament(&mut self, tournament: &mut Tournament) -> Balance {
+        let waitlist = std::mem::take(&mut tournament.waitlist);
+        waitlist.iter().for_each(|player| self.internal_refund_entry(tournament, player));
+
+        let commission = match tournament.status {
+            TournamentState::PENDING_VOLUME => {
+                tournament.players.iter().for_each(|player| self.internal_refund_entry(tournament, player));
+
+                self.internal_refund_sponsors(tournament);
+                tournament.status = TournamentState::EXPIRED;
+                0
+            },
+            TournamentState::DELETED => {
+                self.internal_refund_sponsors(tournament);
+                0
+            },
+            _ => {
+                tournament.prepare_settlement(env::block_timestamp_ms());
+                let plan = self.internal_settlement_plan(tournament);
+                self.internal_release_pool(&tournament.ft_address, tournament.total_stake + tournament.sponsored_pool() + tournament.guaranteed_pool());
+                self.internal_record_tournament_results(tournament, &plan.placements);
+
+                // The commission is shared between the platform, the tournament creator and the players' referrers.
+                // Whatever the prizes leave unallocated goes to the treasury with the platform's share.
+                self.internal_credit_treasury(&tournament.ft_address, plan.split.platform_commission + plan.unallocated);
+                self.internal_credit_creator(&tournament.owner, &tournament.ft_address, plan.split.creator_fee);
+                for (referrer, reward) in plan.split.referral_rewards.iter() {
+                    self.internal_credit_referrer(referrer, &tournament.ft_address, *reward);
+                }
+
+                for (account_id, refund) in plan.guarantee_refunds.iter() {
+                    Self::internal_ft_transfer_or_restore(&tournament.ft_address, account_id, *refund, TransferFallback::STAKE);
+                }
+
+                for (_, account_id, amount) in plan.payouts.iter() {
+                    self.internal_record_winnings(account_id, &tournament.ft_address, *amount);
+                    WordchainEvent::PrizePaid {
+                        tournament_id: tournament.id,
+                        account_id: account_id.clone(),
+                        ft_address: tournament.ft_address.clone(),
+                        amount: U128(*amount),
+                    }.emit();
+
+                    Self::internal_ft_transfer_or_restore(&tournament.ft_address, account_id, *amount, TransferFallback::STAKE);
+                }
+                tournament.players.iter().for_each(Self::internal_refund_entry_deposit);
+                tournament.status = TournamentState::CLOSED;
+                plan.split.platform_commission
             }
+        };
+
+        self.internal_unindex_country_tournament(tournament);
+        WordchainEvent::TournamentClosed { tournament_id: tournament.id, status: tournament.status.clone() }.emit();
+        commission
+    }
+
+    fn internal_transfer_ownership(&mut self, new_owner: AccountId) {
+        WordchainEvent::OwnershipTransferStarted { owner: self.owner.clone(), pending_owner: new_owner.clone() }.emit();
+        self.pending_owner = new_owner;
+    }
+
+    fn internal_set_min_players(&mut self, num: u8) {
+        require!(num > 3, "Minimum number of players must be greater then 3");
+        require!(num as u32 <= self.max_tournament_players, "Minimum number of players can not exceed the maximum");
+        self.min_tournament_players = num;
+        WordchainEvent::config_changed("min_tournament_players", num);
+    }
+
+    fn internal_set_percentage_stake_commission(&mut self, new_value: u64) {
+        self.internal_assert_commission(new_value);
+        self.percentage_stake_commission = new_value;
+        WordchainEvent::config_changed("percentage_stake_commission", new_value);
+    }
+
+    /// Scales a prize curve to basis points of the total stake once the commission is taken.
+    fn internal_position_prizes(prize_curve: &[u64], commission: u64) -> Vec<u64> {
+        let percentage_to_pay: u64 = 10000 - commission;
+        prize_curve.iter().map(|share| share * percentage_to_pay / 10000).collect()
+    }
+
+    /// Activates a pending tournament once it has its minimum players and play has started.
+    fn internal_check_activation(&self, tournament: &mut Tournament) {
+        let play_started = tournament.rounds[0].start <= env::block_timestamp_ms();
+        if tournament.status == TournamentState::PENDING_VOLUME && play_started && tournament.players.len() as u32 >= tournament.min_players {
+            self.internal_activate_tournament(tournament);
         }
-        false
     }
 
-    pub fn get_position_prizes(&self) -> Vec<u64> {
+    fn internal_activate_tournament(&self, tournament: &mut Tournament) {
+        // Mixing in the id keeps tournaments activated in the same block on different puzzles.
+        let seed = env::sha256([env::random_seed(), tournament.id.0.to_le_bytes().to_vec()].concat().as_slice());
 
-        let percentage_to_pay: u64 = 10000 - self.percentage_stake_commission;
-        let mut res = Vec::<u64>::with_capacity(3);
+        tournament.challenge_seed = Some(Base64VecU8::from(seed));
+        tournament.status = TournamentState::ACTIVE;
+        WordchainEvent::TournamentActivated { tournament_id: tournament.id }.emit();
 
-        res.push((5 * percentage_to_pay)/10);
-        res.push((34 * percentage_to_pay)/100);
-        res.push((16* percentage_to_pay)/100);
+        if tournament.format == TournamentFormat::BRACKET {
+            let ratings = tournament.players.iter()
+                .map(|player| self.internal_player_rating(&player.account_id, &tournament.game_type_id))
+                .collect::<Vec<u32>>();
+            tournament.seed_bracket(&ratings);
+        }
+    }
 
-        res
+    fn internal_hash_score(account_id: &AccountId, score: u8, salt: &str) -> CryptoHash {
+        env::sha256_array(format!("{}:{}:{}", account_id, score, salt).as_bytes())
+    }
+
+    fn internal_stakes_prefix(account_id: &AccountId) -> Vec<u8> {
+        [b"s".as_slice(), &env::sha256_array(account_id.as_bytes())].concat()
+    }
+
+    fn internal_debit_stake(&mut self, account_id: &AccountId, ft_address: &AccountId, amount: Balance) {
+        let mut stakes = self.stakes.get(account_id).unwrap_or_else(|| env::panic_str("No stake made"));
+        let ft_stake = stakes.get(ft_address).unwrap_or_default();
+        require!(ft_stake >= amount, "Insufficient stake balance");
+
+        stakes.insert(ft_address, &(ft_stake - amount));
+        self.stakes.insert(account_id, &stakes);
+        self.internal_track_stakes(ft_address, 0, amount);
+    }
+
+    fn internal_credit_stake(&mut self, account_id: &AccountId, ft_address: &AccountId, amount: Balance) {
+        let mut stakes = self.stakes.get(account_id).unwrap_or_else(|| LookupMap::new(Self::internal_stakes_prefix(account_id)));
+        let ft_stake = stakes.get(ft_address).unwrap_or_default();
+
+        stakes.insert(ft_address, &(ft_stake + amount));
+        self.stakes.insert(account_id, &stakes);
+        self.internal_track_stakes(ft_address, amount, 0);
     }
-    
 }
 
+/// What a `ft_transfer_call` is for; an empty message stakes the tokens for the sender like `STAKE` without a referrer.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TransferMessage {
+    STAKE { referrer: Option<AccountId> },
+    SPONSOR { tournament_id: U128, guarantee: Option<bool> },
+}
+
+#[near_bindgen]
 impl FungibleTokenReceiver for Wordchain {
 
     fn ft_on_transfer(&mut self,sender_id:AccountId,amount:U128,msg:String,) -> PromiseOrValue<U128> {
-        let mut stakings = self.stakes.get(&sender_id).unwrap_or(LookupMap::new(b"g"));
-
-        let mut stake_balance = stakings.get(&env::predecessor_account_id()).unwrap_or_default();
+        if !msg.is_empty() {
+            let message = near_sdk::serde_json::from_str::<TransferMessage>(&msg);
+            require!(message.is_ok(), "Invalid transfer message");
+
+            match message.unwrap() {
+                TransferMessage::STAKE { referrer } => self.internal_record_referrer(&sender_id, referrer),
+                TransferMessage::SPONSOR { tournament_id, guarantee } => {
+                    self.internal_sponsor_tournament(tournament_id, sender_id, &env::predecessor_account_id(), amount.0, guarantee.unwrap_or_default());
+                    return PromiseOrValue::Value(U128::from(0));
+                },
+            }
+        }
 
-        stake_balance += amount.0;
-        stakings.insert(&env::predecessor_account_id(), &stake_balance);
-        self.stakes.insert(&sender_id, &stakings);
+        self.internal_credit_stake(&sender_id, &env::predecessor_account_id(), amount.0);
+        WordchainEvent::StakeDeposited { account_id: sender_id, ft_address: env::predecessor_account_id(), amount }.emit();
 
         PromiseOrValue::Value(U128::from(0))
+    }
+}
 
-        // let tournament_staked_for = msg.replace("tournament ", "");
 
-        // let id = U128::try_from(tournament_staked_for);
 
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+
+    const STAKE: Balance = 1_000;
+
+    pub(crate) fn ft() -> AccountId {
+        "usdc.near".parse().unwrap()
     }
-}
 
+    pub(crate) fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .signer_account_id(predecessor_account_id.clone())
+            .predecessor_account_id(predecessor_account_id);
+        builder
+    }
+
+    pub(crate) fn stake(contract: &mut Wordchain, context: &mut VMContextBuilder, account_id: AccountId) {
+        testing_env!(context.predecessor_account_id(ft()).build());
+        contract.ft_on_transfer(account_id, STAKE.into(), String::new());
+    }
+
+    #[derive(Default)]
+    pub(crate) struct TournamentSetup {
+        pub rounds: Option<u32>,
+        pub eliminations: Option<u32>,
+        pub format: Option<TournamentFormat>,
+        pub bracket_seeding: Option<BracketSeeding>,
+        pub team_settings: Option<TeamSettings>,
+        pub eligibility_rules: Option<Vec<EligibilityRule>>,
+        pub min_players: Option<u32>,
+        pub max_players: Option<u32>,
+        pub schedule: Option<ScheduleInput>,
+    }
+
+    pub(crate) fn new_contract(context: &mut VMContextBuilder) -> Wordchain {
+        testing_env!(context.build());
+        let mut contract = Wordchain::new(1500, vec!["NG".to_string(), "GH".to_string()], 4);
+        contract.add_game_type("chain".to_string(), 100);
+        contract
+    }
+
+    /// Creates tournament `daily` as the contract owner, who does not take part in it.
+    pub(crate) fn create_tournament(contract: &mut Wordchain, context: &mut VMContextBuilder, setup: TournamentSetup) -> Tournament {
+        stake(contract, context, accounts(0));
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.create_tournament("daily".to_string(), "key".to_string(), "chain".to_string(), ft(), STAKE.into(), Some(TournamentSettings {
+            rounds: setup.rounds,
+            eliminations_per_round: setup.eliminations,
+            format: setup.format,
+            bracket_seeding: setup.bracket_seeding,
+            team_settings: setup.team_settings,
+            eligibility_rules: setup.eligibility_rules,
+            min_players: setup.min_players,
+            max_players: setup.max_players,
+            schedule: setup.schedule,
+            ..Default::default()
+        }))
+    }
+
+    pub(crate) fn join(contract: &mut Wordchain, context: &mut VMContextBuilder, account_id: AccountId) {
+        stake(contract, context, account_id.clone());
+        testing_env!(context.predecessor_account_id(account_id).build());
+        contract.join_tournament(U128(1), "NG".to_string(), None, None);
+    }
+
+    /// Sets up an active public tournament with id 1, created by the owner and joined by four players.
+    pub(crate) fn setup_active_tournament(context: &mut VMContextBuilder) -> Wordchain {
+        setup_tournament(context, TournamentSetup::default())
+    }
+
+    pub(crate) fn setup_tournament(context: &mut VMContextBuilder, setup: TournamentSetup) -> Wordchain {
+        let mut contract = new_contract(context);
+        create_tournament(&mut contract, context, setup);
+
+        for player in 1..5 {
+            join(&mut contract, context, accounts(player));
+        }
+        contract
+    }
+
+    #[test]
+    fn test_commit_and_reveal_score() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+        assert!(contract.internal_get_tournament(U128(1)).status == TournamentState::ACTIVE);
+
+        let commit = contract.hash_score(accounts(1), 42, "salt".to_string());
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.commit_score(U128(1), commit);
+
+        testing_env!(context.block_timestamp((DAY_TO_MS + 1) * 1_000_000).build());
+        contract.reveal_score(U128(1), 42, "salt".to_string());
+
+        let tournament = contract.internal_get_tournament(U128(1));
+        let player = tournament.players.iter().find(|player| player.account_id == accounts(1)).unwrap();
+        assert!(tournament.status == TournamentState::REVEAL);
+        assert_eq!(player.score, 42);
+        assert_eq!(player.number_of_games_played, 1);
+        assert!(player.score_commits.is_empty());
+    }
+
+    #[test]
+    fn test_total_stake_counts_every_entry() {
+        let mut context = get_context(accounts(0));
+        let contract = setup_active_tournament(&mut context);
+        assert_eq!(contract.internal_get_tournament(U128(1)).total_stake, 4 * STAKE);
+    }
+
+    #[test]
+    fn test_full_tournament_waitlists_players() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup { max_players: Some(4), ..Default::default() });
+
+        for player in 1..6 {
+            join(&mut contract, &mut context, accounts(player));
+        }
+        let tournament = contract.internal_get_tournament(U128(1));
+        assert_eq!(tournament.players.len(), 4);
+        assert_eq!(tournament.total_stake, 4 * STAKE);
+        assert_eq!(contract.get_waitlist(U128(1)), vec![accounts(5)]);
+
+        testing_env!(context.predecessor_account_id(accounts(5)).build());
+        contract.leave_tournament(U128(1));
+        assert!(contract.get_waitlist(U128(1)).is_empty());
+    }
+
+    #[test]
+    fn test_leave_pending_tournament() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup::default());
+        join(&mut contract, &mut context, accounts(1));
+        join(&mut contract, &mut context, accounts(2));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.leave_tournament(U128(1));
+
+        let tournament = contract.internal_get_tournament(U128(1));
+        assert_eq!(tournament.players.len(), 1);
+        assert_eq!(tournament.total_stake, STAKE);
+        assert_eq!(tournament.next_player_id(), U128(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Players can only leave before the tournament is activated")]
+    fn test_leave_active_tournament() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.leave_tournament(U128(1));
+    }
+
+    #[test]
+    fn test_challenge_seed_set_on_activation() {
+        let mut context = get_context(accounts(0));
+        context.random_seed([7; 32]);
+        let contract = setup_active_tournament(&mut context);
+
+        let tournament = contract.internal_get_tournament(U128(1));
+        assert!(tournament.challenge_seed.is_some());
+
+        let first_round = contract.get_challenge_seed(U128(1), 0);
+        assert_eq!(first_round, contract.get_challenge_seed(U128(1), 0));
+        assert_ne!(first_round, contract.get_challenge_seed(U128(1), 1));
+    }
+
+    pub(crate) fn play(contract: &mut Wordchain, context: &mut VMContextBuilder, account_id: AccountId, score: u8) {
+        let commit = contract.hash_score(account_id.clone(), score, "salt".to_string());
+        testing_env!(context.predecessor_account_id(account_id).build());
+        contract.commit_score(U128(1), commit);
+    }
 
+    pub(crate) fn reveal(contract: &mut Wordchain, context: &mut VMContextBuilder, account_id: AccountId, score: u8) {
+        testing_env!(context.predecessor_account_id(account_id).build());
+        contract.reveal_score(U128(1), score, "salt".to_string());
+    }
+
+    #[test]
+    fn test_rounds_eliminate_bottom_players() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_tournament(&mut context, TournamentSetup { rounds: Some(2), eliminations: Some(2), ..Default::default() });
+
+        let schedule = contract.get_round_schedule(U128(1));
+        assert_eq!(schedule.len(), 2);
+        assert_eq!(schedule[1].start, schedule[0].reveal_deadline);
+        assert_eq!(schedule[0].eliminations, 2);
+        assert_eq!(schedule[1].eliminations, 0);
+
+        for player in 1..5 {
+            play(&mut contract, &mut context, accounts(player), player as u8 * 10);
+        }
+        context.block_timestamp(schedule[0].end * 1_000_000);
+        for player in 1..5 {
+            reveal(&mut contract, &mut context, accounts(player), player as u8 * 10);
+        }
+
+        context.block_timestamp(schedule[1].start * 1_000_000);
+        play(&mut contract, &mut context, accounts(3), 5);
+        play(&mut contract, &mut context, accounts(4), 1);
+
+        let tournament = contract.internal_get_tournament(U128(1));
+        let eliminated = tournament.players.iter()
+            .filter(|player| player.eliminated_in_round == Some(0))
+            .map(|player| player.account_id.clone())
+            .collect::<Vec<AccountId>>();
+        assert_eq!(eliminated, vec![accounts(1), accounts(2)]);
+
+        context.block_timestamp(schedule[1].end * 1_000_000);
+        reveal(&mut contract, &mut context, accounts(3), 5);
+        reveal(&mut contract, &mut context, accounts(4), 1);
+
+        let standings = contract.internal_get_tournament(U128(1)).standings();
+        let ranked = standings.iter().map(|player| player.account_id.clone()).collect::<Vec<AccountId>>();
+        assert_eq!(ranked, vec![accounts(3), accounts(4), accounts(2), accounts(1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Player was eliminated from the tournament")]
+    fn test_eliminated_player_can_not_commit() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_tournament(&mut context, TournamentSetup { rounds: Some(2), eliminations: Some(1), ..Default::default() });
+        let schedule = contract.get_round_schedule(U128(1));
+
+        context.block_timestamp(schedule[1].start * 1_000_000);
+        play(&mut contract, &mut context, accounts(1), 5);
+        play(&mut contract, &mut context, accounts(2), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "No commit matches the revealed score")]
+    fn test_reveal_rejects_other_score() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+
+        let commit = contract.hash_score(accounts(1), 42, "salt".to_string());
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.commit_score(U128(1), commit);
+
+        testing_env!(context.block_timestamp((DAY_TO_MS + 1) * 1_000_000).build());
+        contract.reveal_score(U128(1), 90, "salt".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn test_paused_contract_rejects_joins() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup::default());
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.pause_contract();
+        join(&mut contract, &mut context, accounts(1));
+    }
+
+    #[test]
+    fn test_new_commit_replaces_earlier_one() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+
+        play(&mut contract, &mut context, accounts(1), 90);
+        play(&mut contract, &mut context, accounts(1), 42);
+        assert_eq!(contract.internal_get_tournament(U128(1)).players[0].score_commits.len(), 1);
+
+        context.block_timestamp((DAY_TO_MS + 1) * 1_000_000);
+        reveal(&mut contract, &mut context, accounts(1), 42);
+        assert_eq!(contract.internal_get_tournament(U128(1)).players[0].score, 42);
+    }
+
+    #[test]
+    #[should_panic(expected = "No commit matches the revealed score")]
+    fn test_replaced_commit_can_not_be_revealed() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+
+        play(&mut contract, &mut context, accounts(1), 90);
+        play(&mut contract, &mut context, accounts(1), 42);
+
+        context.block_timestamp((DAY_TO_MS + 1) * 1_000_000);
+        reveal(&mut contract, &mut context, accounts(1), 90);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn test_paused_contract_rejects_commits() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.pause_contract();
+
+        play(&mut contract, &mut context, accounts(1), 42);
+    }
+
+    #[test]
+    #[should_panic(expected = "Scores can only be revealed after the round ends")]
+    fn test_reveal_before_deadline() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+
+        let commit = contract.hash_score(accounts(1), 42, "salt".to_string());
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.commit_score(U128(1), commit);
+        contract.reveal_score(U128(1), 42, "salt".to_string());
+    }
+}
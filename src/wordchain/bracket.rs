@@ -0,0 +1,242 @@
+use super::*;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Eq, PartialEq)]
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TournamentFormat {
+    LEADERBOARD,
+    BRACKET,
+}
+
+/// How a bracket orders its players before pairing them into the first round.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Eq, PartialEq)]
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum BracketSeeding {
+    /// Shuffled with the challenge seed.
+    RANDOM,
+    /// By rating in the tournament's game type, the best rated player meeting the worst.
+    RATING,
+}
+
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BracketMatch {
+    round: u32,
+    index: u32,
+    player_one: Option<AccountId>,
+    player_two: Option<AccountId>,
+    winner: Option<AccountId>,
+}
+
+impl BracketMatch {
+    fn new(round: u32, index: u32, player_one: Option<AccountId>, player_two: Option<AccountId>) -> Self {
+        // A bye hands the match to the only seeded player.
+        let winner = match (&player_one, &player_two) {
+            (Some(player), None) | (None, Some(player)) => Some(player.clone()),
+            _ => None,
+        };
+        Self { round, index, player_one, player_two, winner }
+    }
+
+    fn loser(&self) -> Option<AccountId> {
+        let winner = self.winner.as_ref()?;
+        [&self.player_one, &self.player_two].into_iter()
+            .flatten()
+            .find(|player| *player != winner)
+            .cloned()
+    }
+}
+
+impl Tournament {
+    /// Orders the players by the tournament's seeding and pairs them into the first round, padding with byes.
+    /// `ratings` holds each player's rating in the order of `players` and is only read for RATING seeding.
+    pub(crate) fn seed_bracket(&mut self, ratings: &[u32]) {
+        let seed = self.challenge_seed.clone().unwrap_or_else(|| env::panic_str("Tournament has not been activated yet"));
+        let mut players = self.players.iter().map(|player| player.account_id.clone()).collect::<Vec<AccountId>>();
+        let size = players.len().next_power_of_two();
+        let half = size / 2;
+
+        let slots = match self.bracket_seeding {
+            BracketSeeding::RANDOM => {
+                for i in (1..players.len()).rev() {
+                    let draw = env::sha256([seed.0.clone(), (i as u64).to_le_bytes().to_vec()].concat().as_slice());
+                    let j = u64::from_le_bytes(draw[..8].try_into().unwrap()) % (i as u64 + 1);
+                    players.swap(i, j as usize);
+                }
+                (0..size).map(|slot| players.get(slot).cloned()).collect::<Vec<Option<AccountId>>>()
+            }
+            BracketSeeding::RATING => {
+                // Stable sort, so equally rated players keep their join order.
+                let mut seeded = players.into_iter().zip(ratings.iter().copied()).collect::<Vec<(AccountId, u32)>>();
+                seeded.sort_by_key(|(_, rating)| std::cmp::Reverse(*rating));
+                let seeded = seeded.into_iter().map(|(account_id, _)| account_id).collect::<Vec<AccountId>>();
+
+                // Match i pairs seed i with seed size - 1 - i, so the top seeds take the byes.
+                (0..half).map(|index| seeded.get(index).cloned())
+                    .chain((0..half).map(|index| seeded.get(size - 1 - index).cloned()))
+                    .collect()
+            }
+        };
+
+        self.bracket = (0..half).map(|index| BracketMatch::new(
+            0,
+            index as u32,
+            slots[index].clone(),
+            slots[index + half].clone(),
+        )).collect();
+        self.advance_bracket();
+    }
+
+    fn bracket_rounds(&self) -> u32 {
+        self.players.len().next_power_of_two().trailing_zeros()
+    }
+
+    /// Opens the next round matches whose two feeding matches have a winner.
+    fn advance_bracket(&mut self) {
+        for round in 1..self.bracket_rounds() {
+            let matches = (self.players.len().next_power_of_two() >> (round + 1)) as u32;
+
+            for index in 0..matches {
+                if self.bracket_match(round, index).is_some() {
+                    continue;
+                }
+                let player_one = self.bracket_match(round - 1, index * 2).and_then(|game| game.winner.clone());
+                let player_two = self.bracket_match(round - 1, index * 2 + 1).and_then(|game| game.winner.clone());
+
+                if player_one.is_some() && player_two.is_some() {
+                    self.bracket.push(BracketMatch::new(round, index, player_one, player_two));
+                }
+            }
+        }
+    }
+
+    fn bracket_match(&self, round: u32, index: u32) -> Option<&BracketMatch> {
+        self.bracket.iter().find(|game| game.round == round && game.index == index)
+    }
+
+    pub(crate) fn bracket_winner(&self) -> Option<AccountId> {
+        let final_round = self.bracket_rounds().checked_sub(1)?;
+        self.bracket_match(final_round, 0).and_then(|game| game.winner.clone())
+    }
+
+    /// Accounts grouped by placement: the champion, the runner-up, then the losers of each earlier round.
+    pub(crate) fn bracket_placements(&self) -> Vec<Vec<AccountId>> {
+        let mut placements = vec![self.bracket_winner().into_iter().collect::<Vec<AccountId>>()];
+
+        for round in (0..self.bracket_rounds()).rev() {
+            let losers = self.bracket.iter()
+                .filter(|game| game.round == round)
+                .filter_map(|game| game.loser())
+                .collect::<Vec<AccountId>>();
+            placements.push(losers);
+        }
+        placements
+    }
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    //Admin level
+    pub fn report_match_result(&mut self, tournament_id: U128, round: u32, match_index: u32, winner: AccountId) {
+        require!(self.owner == env::predecessor_account_id(), "Unauthorized");
+
+        let mut tournament = self.internal_get_tournament(tournament_id);
+        require!(tournament.format == TournamentFormat::BRACKET, "Tournament is not a bracket");
+        require!(tournament.status == TournamentState::ACTIVE, "Tournament is not active");
+
+        let game = tournament.bracket.iter_mut().find(|game| game.round == round && game.index == match_index);
+        require!(game.is_some(), "Match with provided round and index does not exist");
+
+        let game = game.unwrap();
+        require!(game.winner.is_none(), "Match result was already reported");
+        require!(
+            game.player_one.as_ref() == Some(&winner) || game.player_two.as_ref() == Some(&winner),
+            "Winner must be one of the match players"
+        );
+        game.winner = Some(winner);
+
+        tournament.advance_bracket();
+        self.internal_save_tournament(&tournament);
+    }
+
+    pub fn get_bracket(&self, tournament_id: U128) -> Vec<BracketMatch> {
+        self.internal_get_tournament(tournament_id).bracket
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{create_tournament, get_context, join, new_contract, setup_tournament, TournamentSetup};
+
+    fn report(contract: &mut Wordchain, round: u32, match_index: u32, winner: fn(&BracketMatch) -> Option<AccountId>) -> AccountId {
+        let game = contract.get_bracket(U128(1)).into_iter().find(|game| game.round == round && game.index == match_index).unwrap();
+        let winner = winner(&game).unwrap();
+        contract.report_match_result(U128(1), round, match_index, winner.clone());
+        winner
+    }
+
+    #[test]
+    fn test_bracket_placements() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_tournament(&mut context, TournamentSetup { format: Some(TournamentFormat::BRACKET), ..Default::default() });
+
+        let bracket = contract.get_bracket(U128(1));
+        assert_eq!(bracket.len(), 2);
+        assert!(bracket.iter().all(|game| game.player_one.is_some() && game.player_two.is_some()));
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        let first = report(&mut contract, 0, 0, |game| game.player_one.clone());
+        let second = report(&mut contract, 0, 1, |game| game.player_two.clone());
+
+        let tournament = contract.internal_get_tournament(U128(1));
+        assert!(tournament.bracket_winner().is_none());
+        let final_match = tournament.bracket_match(1, 0).unwrap();
+        assert_eq!(final_match.player_one, Some(first.clone()));
+        assert_eq!(final_match.player_two, Some(second.clone()));
+
+        report(&mut contract, 1, 0, |game| game.player_two.clone());
+        let placements = contract.internal_get_tournament(U128(1)).bracket_placements();
+        assert_eq!(placements[0], vec![second]);
+        assert_eq!(placements[1], vec![first]);
+        assert_eq!(placements[2].len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Winner must be one of the match players")]
+    fn test_report_rejects_outsider() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_tournament(&mut context, TournamentSetup { format: Some(TournamentFormat::BRACKET), ..Default::default() });
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.report_match_result(U128(1), 0, 0, accounts(5));
+    }
+
+    #[test]
+    fn test_rating_seeding_pairs_best_with_worst() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup {
+            format: Some(TournamentFormat::BRACKET),
+            bracket_seeding: Some(BracketSeeding::RATING),
+            ..Default::default()
+        });
+
+        for (player, rating) in [(1, 1000), (2, 1400), (3, 1200), (4, 1300)] {
+            contract.player_profiles.insert(&accounts(player), &PlayerProfile::with_rating("chain", rating));
+            join(&mut contract, &mut context, accounts(player));
+        }
+
+        let bracket = contract.get_bracket(U128(1));
+        assert_eq!(bracket.len(), 2);
+        assert_eq!((bracket[0].player_one.clone(), bracket[0].player_two.clone()), (Some(accounts(2)), Some(accounts(1))));
+        assert_eq!((bracket[1].player_one.clone(), bracket[1].player_two.clone()), (Some(accounts(4)), Some(accounts(3))));
+    }
+}
@@ -0,0 +1,383 @@
+use super::*;
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Eq, PartialEq)]
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ChallengeState {
+    OPEN,
+    ACTIVE,
+    SETTLED,
+    CANCELLED,
+}
+
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ChallengeEntry {
+    account_id: AccountId,
+    score: Option<u64>,
+    #[serde(skip)]
+    score_commit: Option<CryptoHash>,
+}
+
+impl ChallengeEntry {
+    fn new(account_id: AccountId) -> Self {
+        Self { account_id, score: None, score_commit: None }
+    }
+}
+
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Challenge {
+    id: U128,
+    game_type_id: String,
+    ft_address: AccountId,
+    stake: U128,
+    /// Basis points of the pot taken as platform commission, fixed when the challenge is created.
+    commission: u64,
+    creator: ChallengeEntry,
+    opponent: Option<ChallengeEntry>,
+    invited: Option<AccountId>,
+    created_at: u64,
+    accept_deadline: u64,
+    play_deadline: u64,
+    reveal_deadline: u64,
+    challenge_seed: Option<Base64VecU8>,
+    winner: Option<AccountId>,
+    status: ChallengeState,
+}
+
+impl Challenge {
+    fn entry_mut(&mut self, account_id: &AccountId) -> Option<&mut ChallengeEntry> {
+        if &self.creator.account_id == account_id {
+            return Some(&mut self.creator);
+        }
+        self.opponent.as_mut().filter(|entry| &entry.account_id == account_id)
+    }
+
+    fn both_revealed(&self) -> bool {
+        self.creator.score.is_some() && self.opponent.as_ref().is_some_and(|entry| entry.score.is_some())
+    }
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    /// Stakes `amount` from the caller's deposited balance against `opponent`, or anyone when left open.
+    pub fn create_challenge(&mut self,
+        game_type_id: String,
+        ft_address: AccountId,
+        amount: U128,
+        opponent: Option<AccountId>,
+        accept_within: Option<u64>,
+    ) -> Challenge {
+        require!(!self.paused, "Contract is paused");
+        require!(amount.0 > 0, "Challenge stake must be greater than 0");
+        require!(!self.get_gametypes(Some(game_type_id.clone())).is_empty(), "No tournament with provided game type");
+        require!(opponent.as_ref() != Some(&env::predecessor_account_id()), "You can not challenge yourself");
+
+        self.internal_debit_stake(&env::predecessor_account_id(), &ft_address, amount.0);
+        self.internal_lock_pool(&ft_address, amount.0);
+
+        let now = env::block_timestamp_ms();
+        let accept_deadline = now.checked_add(accept_within.unwrap_or(DAY_TO_MS));
+        require!(accept_deadline.is_some(), "Accept window is too long");
+        let challenge = Challenge {
+            id: U128::from((self.challenges.len() as u128) + 1),
+            commission: self.internal_commission_rate(&ft_address, &game_type_id),
+            game_type_id,
+            ft_address,
+            stake: amount,
+            creator: ChallengeEntry::new(env::predecessor_account_id()),
+            opponent: None,
+            invited: opponent,
+            created_at: now,
+            accept_deadline: accept_deadline.unwrap(),
+            play_deadline: 0,
+            reveal_deadline: 0,
+            challenge_seed: None,
+            winner: None,
+            status: ChallengeState::OPEN,
+        };
+
+        self.challenges.push(&challenge);
+        WordchainEvent::ChallengeCreated {
+            challenge_id: challenge.id,
+            creator: challenge.creator.account_id.clone(),
+            invited: challenge.invited.clone(),
+            ft_address: challenge.ft_address.clone(),
+            stake: challenge.stake,
+        }.emit();
+        challenge
+    }
+
+    /// Matches the challenge stake, which starts the play window on a freshly drawn seed.
+    pub fn accept_challenge(&mut self, challenge_id: U128) {
+        require!(!self.paused, "Contract is paused");
+        let mut challenge = self.internal_get_challenge(challenge_id);
+        require!(challenge.status == ChallengeState::OPEN, "Challenge is not open");
+        require!(challenge.accept_deadline > env::block_timestamp_ms(), "Challenge exceeded the accept deadline");
+        require!(challenge.creator.account_id != env::predecessor_account_id(), "You can not accept your own challenge");
+        if let Some(invited) = &challenge.invited {
+            require!(invited == &env::predecessor_account_id(), "Challenge is reserved for another player");
+        }
+
+        self.internal_debit_stake(&env::predecessor_account_id(), &challenge.ft_address, challenge.stake.0);
+        self.internal_lock_pool(&challenge.ft_address, challenge.stake.0);
+
+        let seed = env::sha256([env::random_seed(), b"challenge".to_vec(), challenge.id.0.to_le_bytes().to_vec()].concat().as_slice());
+        let now = env::block_timestamp_ms();
+
+        challenge.opponent = Some(ChallengeEntry::new(env::predecessor_account_id()));
+        challenge.challenge_seed = Some(Base64VecU8::from(seed));
+        challenge.play_deadline = now + DAY_TO_MS;
+        challenge.reveal_deadline = challenge.play_deadline + self.reveal_window;
+        challenge.status = ChallengeState::ACTIVE;
+        self.internal_save_challenge(&challenge);
+        WordchainEvent::ChallengeAccepted { challenge_id: challenge.id, account_id: env::predecessor_account_id() }.emit();
+    }
+
+    pub fn commit_challenge_score(&mut self, challenge_id: U128, score_hash: Base64VecU8) {
+        require!(!self.paused, "Contract is paused");
+        let mut challenge = self.internal_get_challenge(challenge_id);
+        require!(challenge.status == ChallengeState::ACTIVE, "Challenge is not active");
+        require!(challenge.play_deadline > env::block_timestamp_ms(), "Challenge exceeded the play deadline");
+
+        let score_hash: CryptoHash = score_hash.0.try_into().unwrap_or_else(|_| env::panic_str("Score hash must be 32 bytes"));
+        let entry = challenge.entry_mut(&env::predecessor_account_id());
+        require!(entry.is_some(), "Only challenge players can commit scores");
+
+        let entry = entry.unwrap();
+        require!(entry.score_commit.is_none(), "Score was already committed");
+        entry.score_commit = Some(score_hash);
+        self.internal_save_challenge(&challenge);
+    }
+
+    /// Reveals are accepted as soon as both players committed, or after the play deadline.
+    pub fn reveal_challenge_score(&mut self, challenge_id: U128, score: u8, salt: String) {
+        require!(!self.paused, "Contract is paused");
+        let mut challenge = self.internal_get_challenge(challenge_id);
+        require!(challenge.status == ChallengeState::ACTIVE, "Challenge is not active");
+        require!(challenge.reveal_deadline > env::block_timestamp_ms(), "Challenge exceeded the reveal deadline");
+
+        let both_committed = challenge.creator.score_commit.is_some()
+            && challenge.opponent.as_ref().is_some_and(|entry| entry.score_commit.is_some());
+        require!(both_committed || challenge.play_deadline <= env::block_timestamp_ms(), "Scores can only be revealed once both players committed");
+
+        let gametype = self.get_gametypes(Some(challenge.game_type_id.clone()));
+        require!(score as u64 <= gametype[0].max_score, "Score exceeds threshold for game");
+
+        let score_hash = Self::internal_hash_score(&env::predecessor_account_id(), score, &salt);
+        let entry = challenge.entry_mut(&env::predecessor_account_id());
+        require!(entry.is_some(), "Only challenge players can reveal scores");
+
+        let entry = entry.unwrap();
+        require!(entry.score.is_none(), "Score was already revealed");
+        require!(entry.score_commit == Some(score_hash), "No commit matches the revealed score");
+        entry.score = Some(score as u64);
+        self.internal_save_challenge(&challenge);
+    }
+
+    /// Pays the pot minus commission to the winner once both scores are in or the reveal window closed.
+    /// An unaccepted challenge refunds the creator after its accept deadline.
+    pub fn settle_challenge(&mut self, challenge_id: U128) {
+        let mut challenge = self.internal_get_challenge(challenge_id);
+        let now = env::block_timestamp_ms();
+
+        match challenge.status {
+            ChallengeState::OPEN => {
+                require!(
+                    challenge.accept_deadline <= now || challenge.creator.account_id == env::predecessor_account_id(),
+                    "Challenge can still be accepted"
+                );
+                self.internal_release_pool(&challenge.ft_address, challenge.stake.0);
+                self.internal_credit_stake(&challenge.creator.account_id, &challenge.ft_address, challenge.stake.0);
+                challenge.status = ChallengeState::CANCELLED;
+            },
+            ChallengeState::ACTIVE => {
+                require!(challenge.both_revealed() || challenge.reveal_deadline <= now, "Challenge scores are still being revealed");
+
+                let opponent = challenge.opponent.clone().unwrap();
+                let pot = challenge.stake.0 * 2;
+                let commission = (challenge.commission as u128 * pot) / 10000;
+                self.internal_release_pool(&challenge.ft_address, pot);
+
+                let winners = match (challenge.creator.score, opponent.score) {
+                    (Some(creator), Some(opponent_score)) if creator > opponent_score => vec![challenge.creator.account_id.clone()],
+                    (Some(creator), Some(opponent_score)) if creator < opponent_score => vec![opponent.account_id.clone()],
+                    (Some(_), None) => vec![challenge.creator.account_id.clone()],
+                    (None, Some(_)) => vec![opponent.account_id.clone()],
+                    // A draw splits the pot, a challenge nobody revealed refunds both stakes.
+                    (Some(_), Some(_)) => vec![challenge.creator.account_id.clone(), opponent.account_id.clone()],
+                    (None, None) => Vec::new(),
+                };
+
+                if winners.is_empty() {
+                    self.internal_credit_stake(&challenge.creator.account_id, &challenge.ft_address, challenge.stake.0);
+                    self.internal_credit_stake(&opponent.account_id, &challenge.ft_address, challenge.stake.0);
+                    challenge.status = ChallengeState::CANCELLED;
+                } else {
+                    // What an uneven draw split leaves over goes to the treasury along with the commission.
+                    let val_to_pay = (pot - commission) / winners.len() as u128;
+                    self.internal_credit_treasury(&challenge.ft_address, pot - val_to_pay * winners.len() as u128);
+                    winners.iter().for_each(|account_id| {
+                        Self::internal_ft_transfer_or_restore(&challenge.ft_address, account_id, val_to_pay, TransferFallback::STAKE);
+                    });
+
+                    if winners.len() == 1 {
+                        challenge.winner = winners.into_iter().next();
+                    }
+                    challenge.status = ChallengeState::SETTLED;
+                }
+            },
+            _ => env::panic_str("Challenge is already settled"),
+        }
+
+        self.internal_save_challenge(&challenge);
+        WordchainEvent::ChallengeSettled { challenge_id: challenge.id, status: challenge.status.clone(), winner: challenge.winner.clone() }.emit();
+    }
+
+    pub fn get_challenge(&self, challenge_id: U128) -> Challenge {
+        self.internal_get_challenge(challenge_id)
+    }
+
+    pub fn get_open_challenges(&self, account_id: Option<AccountId>) -> Vec<Challenge> {
+        self.challenges.iter()
+            .filter(|challenge| challenge.status == ChallengeState::OPEN)
+            .filter(|challenge| account_id.is_none() || challenge.invited.is_none() || challenge.invited == account_id)
+            .collect()
+    }
+}
+
+impl Wordchain {
+
+    fn internal_get_challenge(&self, challenge_id: U128) -> Challenge {
+        challenge_id.0.checked_sub(1)
+            .and_then(|index| self.challenges.get(index as u64))
+            .unwrap_or_else(|| env::panic_str("Challenge with provided ID does not exist"))
+    }
+
+    fn internal_save_challenge(&mut self, challenge: &Challenge) {
+        self.challenges.replace((challenge.id.0 - 1) as u64, challenge);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{ft, get_context, new_contract, stake};
+
+    /// Players 1 and 2 staked, with player 1 challenging player 2 for `amount` and player 2 accepting.
+    fn setup_accepted_challenge(context: &mut VMContextBuilder, amount: Balance) -> Wordchain {
+        let mut contract = new_contract(context);
+        stake(&mut contract, context, accounts(1));
+        stake(&mut contract, context, accounts(2));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.create_challenge("chain".to_string(), ft(), U128(amount), Some(accounts(2)), None);
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.accept_challenge(U128(1));
+        contract
+    }
+
+    fn play(contract: &mut Wordchain, context: &mut VMContextBuilder, account_id: AccountId, score: u8) {
+        let commit = contract.hash_score(account_id.clone(), score, "salt".to_string());
+        testing_env!(context.predecessor_account_id(account_id).build());
+        contract.commit_challenge_score(U128(1), commit);
+    }
+
+    #[test]
+    fn test_challenge_winner_takes_pot() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_accepted_challenge(&mut context, 500);
+        play(&mut contract, &mut context, accounts(1), 30);
+        play(&mut contract, &mut context, accounts(2), 40);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.reveal_challenge_score(U128(1), 30, "salt".to_string());
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.reveal_challenge_score(U128(1), 40, "salt".to_string());
+        contract.settle_challenge(U128(1));
+
+        let challenge = contract.get_challenge(U128(1));
+        assert!(challenge.status == ChallengeState::SETTLED);
+        assert_eq!(challenge.winner, Some(accounts(2)));
+        assert_eq!(contract.stake_payouts.get(&ft()), Some(150));
+        assert_eq!(
+            get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"wordchain","version":"1.0.0","event":"challenge_settled","data":{"challenge_id":"1","status":"SETTLED","winner":"charlie"}}"#
+        );
+    }
+
+    #[test]
+    fn test_draw_remainder_credited_to_treasury() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_accepted_challenge(&mut context, 505);
+        play(&mut contract, &mut context, accounts(1), 30);
+        play(&mut contract, &mut context, accounts(2), 30);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.reveal_challenge_score(U128(1), 30, "salt".to_string());
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.reveal_challenge_score(U128(1), 30, "salt".to_string());
+        contract.settle_challenge(U128(1));
+
+        // 151 of commission on the 1010 pot and 1 left over from splitting the other 859.
+        assert_eq!(contract.stake_payouts.get(&ft()), Some(152));
+        assert_eq!(contract.get_challenge(U128(1)).winner, None);
+    }
+
+    #[test]
+    fn test_unaccepted_challenge_refunds() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        stake(&mut contract, &mut context, accounts(1));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.create_challenge("chain".to_string(), ft(), U128(500), None, Some(1000));
+        assert_eq!(contract.stakes.get(&accounts(1)).unwrap().get(&ft()), Some(500));
+
+        testing_env!(context.predecessor_account_id(accounts(3)).block_timestamp(2_000 * 1_000_000).build());
+        contract.settle_challenge(U128(1));
+
+        assert!(contract.get_challenge(U128(1)).status == ChallengeState::CANCELLED);
+        assert_eq!(contract.stakes.get(&accounts(1)).unwrap().get(&ft()), Some(1000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Challenge is reserved for another player")]
+    fn test_only_invited_opponent_accepts() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        stake(&mut contract, &mut context, accounts(1));
+        stake(&mut contract, &mut context, accounts(3));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.create_challenge("chain".to_string(), ft(), U128(500), Some(accounts(2)), None);
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.accept_challenge(U128(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn test_paused_contract_rejects_challenge_commits() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_accepted_challenge(&mut context, 500);
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.pause_contract();
+
+        play(&mut contract, &mut context, accounts(1), 42);
+    }
+
+    #[test]
+    #[should_panic(expected = "Accept window is too long")]
+    fn test_accept_window_overflow_rejected() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        stake(&mut contract, &mut context, accounts(1));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp(1_000_000).build());
+        contract.create_challenge("chain".to_string(), ft(), U128(500), None, Some(u64::MAX));
+    }
+}
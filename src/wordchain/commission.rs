@@ -0,0 +1,155 @@
+use super::*;
+
+/// Bounds every commission rate, default or override, must stay within until the admins change them.
+pub const DEFAULT_MIN_COMMISSION: u64 = 1000;
+pub const DEFAULT_MAX_COMMISSION: u64 = 5000;
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CommissionSettings {
+    default: u64,
+    min: u64,
+    max: u64,
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    pub fn get_commission_settings(&self) -> CommissionSettings {
+        CommissionSettings { default: self.percentage_stake_commission, min: self.min_commission, max: self.max_commission }
+    }
+
+    /// The rate, in basis points, a tournament created now for this token and game type would snapshot.
+    pub fn get_commission_rate(&self, ft_address: AccountId, game_type_id: String) -> u64 {
+        self.internal_commission_rate(&ft_address, &game_type_id)
+    }
+
+    pub fn set_commission_bounds(&mut self, min: u64, max: u64) {
+        self.internal_assert_owner_action();
+        self.internal_set_commission_bounds(min, max);
+    }
+
+    /// Overrides the default rate for tournaments played for `ft_address`; `None` removes the override.
+    pub fn set_token_commission(&mut self, ft_address: AccountId, commission: Option<u64>) {
+        self.internal_assert_owner_action();
+        self.internal_set_token_commission(ft_address, commission);
+    }
+
+    /// Overrides the default and token rates for tournaments of `game_type_id`; `None` removes the override.
+    pub fn set_game_type_commission(&mut self, game_type_id: String, commission: Option<u64>) {
+        self.internal_assert_owner_action();
+        self.internal_set_game_type_commission(game_type_id, commission);
+    }
+}
+
+impl Wordchain {
+
+    /// A game type override takes precedence over a token override, which takes precedence over the default.
+    pub(crate) fn internal_commission_rate(&self, ft_address: &AccountId, game_type_id: &str) -> u64 {
+        self.game_type_commissions.get(&game_type_id.to_string())
+            .or_else(|| self.token_commissions.get(ft_address))
+            .unwrap_or(self.percentage_stake_commission)
+    }
+
+    pub(crate) fn internal_assert_commission(&self, commission: u64) {
+        require!(
+            commission >= self.min_commission && commission <= self.max_commission,
+            format!("Commission must be between {} and {} basis points", self.min_commission, self.max_commission)
+        );
+    }
+
+    /// Overrides already outside new bounds keep applying until they are changed; only the default is checked.
+    pub(crate) fn internal_set_commission_bounds(&mut self, min: u64, max: u64) {
+        require!(min <= max && max <= 10000, "Commission bounds must satisfy min <= max <= 10000");
+        require!(
+            self.percentage_stake_commission >= min && self.percentage_stake_commission <= max,
+            "Default commission must be within the new bounds"
+        );
+        self.min_commission = min;
+        self.max_commission = max;
+        WordchainEvent::config_changed("commission_bounds", format!("{}:{}", min, max));
+    }
+
+    pub(crate) fn internal_set_token_commission(&mut self, ft_address: AccountId, commission: Option<u64>) {
+        match commission {
+            Some(commission) => {
+                self.internal_assert_commission(commission);
+                self.token_commissions.insert(&ft_address, &commission);
+            },
+            None => {
+                self.token_commissions.remove(&ft_address);
+            },
+        }
+        WordchainEvent::config_changed("token_commission", format!("{}:{}", ft_address, commission.unwrap_or_default()));
+    }
+
+    pub(crate) fn internal_set_game_type_commission(&mut self, game_type_id: String, commission: Option<u64>) {
+        match commission {
+            Some(commission) => {
+                self.internal_assert_commission(commission);
+                self.game_type_commissions.insert(&game_type_id, &commission);
+            },
+            None => {
+                self.game_type_commissions.remove(&game_type_id);
+            },
+        }
+        WordchainEvent::config_changed("game_type_commission", format!("{}:{}", game_type_id, commission.unwrap_or_default()));
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{create_tournament, ft, get_context, new_contract, TournamentSetup};
+
+    #[test]
+    fn test_overrides_take_precedence() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+
+        contract.set_token_commission(ft(), Some(2000));
+        assert_eq!(contract.get_commission_rate(ft(), "chain".to_string()), 2000);
+        contract.set_game_type_commission("chain".to_string(), Some(2500));
+        assert_eq!(contract.get_commission_rate(ft(), "chain".to_string()), 2500);
+        assert_eq!(contract.get_commission_rate(accounts(5), "other".to_string()), 1500);
+
+        contract.set_game_type_commission("chain".to_string(), None);
+        assert_eq!(contract.get_commission_rate(ft(), "chain".to_string()), 2000);
+    }
+
+    #[test]
+    fn test_commission_snapshotted_at_creation() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_token_commission(ft(), Some(2000));
+        create_tournament(&mut contract, &mut context, TournamentSetup::default());
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_token_commission(ft(), Some(3000));
+        contract.set_percentage_stake_commission(4000);
+        assert_eq!(contract.internal_get_tournament(U128(1)).commission, 2000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Commission must be between 1000 and 5000 basis points")]
+    fn test_commission_above_max() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_percentage_stake_commission(10001);
+    }
+
+    #[test]
+    #[should_panic(expected = "Default commission must be within the new bounds")]
+    fn test_bounds_must_cover_default() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_commission_bounds(2000, 3000);
+    }
+}
@@ -0,0 +1,167 @@
+use super::*;
+
+/// Officially assigned ISO 3166-1 alpha-2 codes.
+const ISO_3166_ALPHA_2: [&str; 249] = [
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+    "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+    "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+    "EG", "EH", "ER", "ES", "ET", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE", "GF",
+    "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK", "HM",
+    "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE", "JM",
+    "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB", "LC",
+    "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH", "MK",
+    "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ", "NA",
+    "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF", "PG",
+    "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU", "RW",
+    "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR", "SS",
+    "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN", "TO",
+    "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG", "VI",
+    "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+/// The upper cased code if it is an assigned ISO 3166-1 alpha-2 code.
+pub(crate) fn normalize_country(country_code: &str) -> Option<String> {
+    let code = country_code.trim().to_ascii_uppercase();
+    ISO_3166_ALPHA_2.binary_search(&code.as_str()).is_ok().then_some(code)
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    /// Adds the codes that are not supported yet; every code must be a valid ISO 3166-1 alpha-2 code.
+    pub fn add_supported_countries(&mut self, countries: Vec<String>) {
+        require!(self.owner == env::predecessor_account_id(), "Unauthorized");
+        let added = self.internal_add_supported_countries(countries);
+        WordchainEvent::config_changed("supported_countries_added", added.join("|"));
+    }
+
+    /// Removes the codes, which must not have tournaments still running.
+    pub fn remove_supported_countries(&mut self, countries: Vec<String>) {
+        require!(self.owner == env::predecessor_account_id(), "Unauthorized");
+        let mut removed = Vec::new();
+        for country in countries {
+            let code = normalize_country(&country).unwrap_or(country);
+            require!(
+                self.country_tournaments.get(&code).unwrap_or_default().is_empty(),
+                format!("Country {} has active tournaments", code)
+            );
+            if self.supported_countries.remove(&code) {
+                removed.push(code);
+            }
+        }
+        WordchainEvent::config_changed("supported_countries_removed", removed.join("|"));
+    }
+
+    pub fn check_supported_country(&self, country_code: String) -> bool {
+        normalize_country(&country_code).is_some_and(|code| self.supported_countries.contains(&code))
+    }
+
+    pub fn get_supported_countries(&self, from_index: Option<u64>, limit: Option<u64>) -> Vec<String> {
+        self.supported_countries.iter()
+            .skip(from_index.unwrap_or_default() as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+
+    /// Country based tournaments of `country_code` that have not been closed yet.
+    pub fn get_country_tournaments(&self, country_code: String) -> Vec<Tournament> {
+        normalize_country(&country_code)
+            .and_then(|code| self.country_tournaments.get(&code))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|tournament_id| self.internal_get_tournament(tournament_id))
+            .collect()
+    }
+}
+
+impl Wordchain {
+
+    /// Returns the codes that were newly added.
+    pub(crate) fn internal_add_supported_countries(&mut self, countries: Vec<String>) -> Vec<String> {
+        countries.into_iter().filter_map(|country| {
+            let code = normalize_country(&country);
+            require!(code.is_some(), format!("{} is not an ISO 3166-1 alpha-2 country code", country));
+            let code = code.unwrap();
+            self.supported_countries.insert(&code).then_some(code)
+        }).collect()
+    }
+
+    pub(crate) fn internal_index_country_tournament(&mut self, tournament: &Tournament) {
+        if tournament.country.is_empty() {
+            return;
+        }
+        let mut tournaments = self.country_tournaments.get(&tournament.country).unwrap_or_default();
+        tournaments.push(tournament.id);
+        self.country_tournaments.insert(&tournament.country, &tournaments);
+    }
+
+    pub(crate) fn internal_unindex_country_tournament(&mut self, tournament: &Tournament) {
+        if let Some(mut tournaments) = self.country_tournaments.get(&tournament.country) {
+            tournaments.retain(|tournament_id| tournament_id != &tournament.id);
+            if tournaments.is_empty() {
+                self.country_tournaments.remove(&tournament.country);
+            } else {
+                self.country_tournaments.insert(&tournament.country, &tournaments);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{ft, get_context, new_contract, stake};
+
+    #[test]
+    fn test_iso_codes_sorted() {
+        assert!(ISO_3166_ALPHA_2.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_add_and_remove_countries() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+
+        contract.add_supported_countries(vec!["ke".to_string(), "NG".to_string(), "KE".to_string()]);
+        assert_eq!(contract.get_supported_countries(None, None), vec!["NG", "GH", "KE"]);
+        assert_eq!(contract.get_supported_countries(Some(1), Some(1)), vec!["GH"]);
+        assert!(contract.check_supported_country("ke".to_string()));
+
+        contract.remove_supported_countries(vec!["GH".to_string()]);
+        assert!(!contract.check_supported_country("GH".to_string()));
+        assert_eq!(contract.get_supported_countries(None, None).len(), 2);
+    }
+
+    #[test]
+    fn test_country_tournaments_indexed_until_closed() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        stake(&mut contract, &mut context, accounts(0));
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.create_tournament("daily".to_string(), "key".to_string(), "chain".to_string(), ft(), U128(1000), Some(TournamentSettings {
+            form: Some(TournamentType::COUNTRY_BASED),
+            country: Some("gh".to_string()),
+            ..Default::default()
+        }));
+        assert_eq!(contract.get_country_tournaments("GH".to_string()).len(), 1);
+        assert!(contract.get_country_tournaments("NG".to_string()).is_empty());
+
+        testing_env!(context.predecessor_account_id(accounts(5)).block_timestamp(DAY_TO_MS * 1_000_000).build());
+        contract.advance_tournament(U128(1));
+        assert!(contract.get_country_tournaments("GH".to_string()).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "XX is not an ISO 3166-1 alpha-2 country code")]
+    fn test_rejects_unknown_code() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.add_supported_countries(vec!["XX".to_string()]);
+    }
+}
@@ -0,0 +1,178 @@
+use super::*;
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum EligibilityRule {
+    RATING_RANGE(RatingRange),
+    MIN_TOURNAMENTS_PLAYED(u64),
+    /// Minimum WCT the player has deposited with this contract, not what they hold in their wallet.
+    MIN_WCT_DEPOSIT(U128),
+    ALLOWLIST(Vec<AccountId>),
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EligibilityCheck {
+    eligible: bool,
+    reasons: Vec<String>,
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    /// Evaluates every join requirement of the tournament for `account_id` except the key and country passed to `join_tournament`.
+    pub fn check_eligibility(&self, tournament_id: U128, account_id: AccountId) -> EligibilityCheck {
+        let reasons = self.internal_eligibility_failures(&self.internal_get_tournament(tournament_id), &account_id);
+        EligibilityCheck { eligible: reasons.is_empty(), reasons }
+    }
+
+    /// Adds accounts to the tournament allowlist, creating one if the tournament had none.
+    pub fn invite_players(&mut self, tournament_id: U128, account_ids: Vec<AccountId>) {
+        let mut tournament = self.internal_get_tournament(tournament_id);
+        require!(tournament.owner == env::predecessor_account_id(), "Only the tournament owner can invite players");
+
+        let allowlist = tournament.eligibility_rules.iter_mut().find_map(|rule| match rule {
+            EligibilityRule::ALLOWLIST(allowlist) => Some(allowlist),
+            _ => None,
+        });
+        match allowlist {
+            Some(allowlist) => account_ids.into_iter()
+                .for_each(|account_id| if !allowlist.contains(&account_id) { allowlist.push(account_id) }),
+            None => tournament.eligibility_rules.push(EligibilityRule::ALLOWLIST(account_ids)),
+        }
+        self.internal_save_tournament(&tournament);
+    }
+
+    pub fn set_wct_token(&mut self, wct_token: AccountId) {
+        require!(self.owner == env::predecessor_account_id(), "Unauthorized");
+        WordchainEvent::config_changed("wct_token", &wct_token);
+        self.wct_token = Some(wct_token);
+    }
+}
+
+impl Wordchain {
+
+    pub(crate) fn internal_eligibility_failures(&self, tournament: &Tournament, account_id: &AccountId) -> Vec<String> {
+        let mut failures = Vec::new();
+
+        if &tournament.owner == account_id {
+            failures.push("Tournament owner can not join the tournament".to_string());
+        }
+        if let Some(failure) = tournament.registration.failure(env::block_timestamp_ms()) {
+            failures.push(failure.to_string());
+        }
+        if tournament.format == TournamentFormat::BRACKET && tournament.status != TournamentState::PENDING_VOLUME {
+            failures.push("Bracket has already been seeded".to_string());
+        }
+        if tournament.players.iter().chain(tournament.waitlist.iter()).any(|player| &player.account_id == account_id) {
+            failures.push("Player already joined the tournament".to_string());
+        }
+
+        for rule in tournament.eligibility_rules.iter() {
+            match rule {
+                EligibilityRule::RATING_RANGE(range) => {
+                    let rating = self.internal_player_rating(account_id, &tournament.game_type_id);
+                    if !range.contains(rating) {
+                        failures.push(format!("Player rating {} is outside the tournament rating range", rating));
+                    }
+                },
+                EligibilityRule::MIN_TOURNAMENTS_PLAYED(minimum) => {
+                    let played = self.get_player_profile(account_id.clone()).tournaments_played();
+                    if played < *minimum {
+                        failures.push(format!("Player must have played at least {} tournaments, played {}", minimum, played));
+                    }
+                },
+                EligibilityRule::MIN_WCT_DEPOSIT(minimum) => {
+                    let balance = self.wct_token.as_ref()
+                        .and_then(|wct_token| self.stakes.get(account_id).and_then(|stakes| stakes.get(wct_token)))
+                        .unwrap_or_default();
+                    if balance < minimum.0 {
+                        failures.push(format!("Player must have deposited at least {} WCT with the contract", minimum.0));
+                    }
+                },
+                EligibilityRule::ALLOWLIST(allowlist) => {
+                    if !allowlist.contains(account_id) {
+                        failures.push("Player is not invited to the tournament".to_string());
+                    }
+                },
+            }
+        }
+        failures
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{create_tournament, ft, get_context, join, new_contract, TournamentSetup};
+
+    #[test]
+    fn test_check_eligibility_lists_every_failure() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup {
+            eligibility_rules: Some(vec![
+                EligibilityRule::MIN_TOURNAMENTS_PLAYED(2),
+                EligibilityRule::ALLOWLIST(vec![accounts(2)]),
+            ]),
+            ..Default::default()
+        });
+
+        let check = contract.check_eligibility(U128(1), accounts(1));
+        assert!(!check.eligible);
+        assert_eq!(check.reasons, vec![
+            "Player must have played at least 2 tournaments, played 0".to_string(),
+            "Player is not invited to the tournament".to_string(),
+        ]);
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.invite_players(U128(1), vec![accounts(1)]);
+        assert_eq!(contract.check_eligibility(U128(1), accounts(1)).reasons.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Player is not invited to the tournament")]
+    fn test_join_enforces_rules() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup {
+            eligibility_rules: Some(vec![EligibilityRule::ALLOWLIST(vec![accounts(1)])]),
+            ..Default::default()
+        });
+
+        join(&mut contract, &mut context, accounts(1));
+        join(&mut contract, &mut context, accounts(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "WCT deposit rule requires a WCT token to be configured")]
+    fn test_wct_rule_requires_wct_token() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup {
+            eligibility_rules: Some(vec![EligibilityRule::MIN_WCT_DEPOSIT(U128(500))]),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_wct_rule_checks_deposited_wct() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        contract.set_wct_token(ft());
+        create_tournament(&mut contract, &mut context, TournamentSetup {
+            eligibility_rules: Some(vec![EligibilityRule::MIN_WCT_DEPOSIT(U128(500))]),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            contract.check_eligibility(U128(1), accounts(1)).reasons,
+            vec!["Player must have deposited at least 500 WCT with the contract".to_string()]
+        );
+        join(&mut contract, &mut context, accounts(1));
+    }
+}
@@ -0,0 +1,90 @@
+use super::*;
+
+pub const EVENT_STANDARD: &str = "wordchain";
+pub const EVENT_VERSION: &str = "1.0.0";
+
+/// NEP-297 events logged as `EVENT_JSON:{"standard":"wordchain","version":"1.0.0","event":...,"data":...}`.
+#[allow(clippy::enum_variant_names)]
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde", tag = "event", content = "data", rename_all = "snake_case")]
+pub enum WordchainEvent {
+    TournamentCreated { tournament_id: U128, name: String, owner: AccountId, ft_address: AccountId, minimum_stake: U128 },
+    PlayerJoined { tournament_id: U128, account_id: AccountId, stake: U128, waitlisted: bool },
+    ScorePublished { tournament_id: U128, account_id: AccountId, round: u32, score: u8 },
+    TournamentActivated { tournament_id: U128 },
+    TournamentClosed { tournament_id: U128, status: TournamentState },
+    PrizePaid { tournament_id: U128, account_id: AccountId, ft_address: AccountId, amount: U128 },
+    ChallengeCreated { challenge_id: U128, creator: AccountId, invited: Option<AccountId>, ft_address: AccountId, stake: U128 },
+    ChallengeAccepted { challenge_id: U128, account_id: AccountId },
+    ChallengeSettled { challenge_id: U128, status: ChallengeState, winner: Option<AccountId> },
+    StakeDeposited { account_id: AccountId, ft_address: AccountId, amount: U128 },
+    StakeWithdrawn { account_id: AccountId, ft_address: AccountId, amount: U128 },
+    TransferFailed { receiver_id: AccountId, ft_address: AccountId, amount: U128 },
+    CommissionWithdrawn { to: AccountId, ft_address: AccountId, amount: U128 },
+    ConfigChanged { parameter: String, value: String },
+    OwnershipTransferStarted { owner: AccountId, pending_owner: AccountId },
+    OwnershipTransferred { previous_owner: AccountId, owner: AccountId },
+    ProposalCreated { id: u64, proposer: AccountId },
+    ProposalApproved { id: u64, account_id: AccountId },
+    ProposalExecuted { id: u64 },
+    ProposalCancelled { id: u64 },
+    CodeStaged { sha256: String, deployable_at: u64 },
+    CodeDeployed { sha256: String },
+    SolvencyChecked { ft_address: AccountId, balance: U128, liabilities: U128, surplus: U128, deficit: U128 },
+    LiabilityDeficit { ft_address: AccountId, liability: String, deficit: U128 },
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a WordchainEvent,
+}
+
+impl WordchainEvent {
+    pub(crate) fn emit(&self) {
+        let log = EventLog { standard: EVENT_STANDARD, version: EVENT_VERSION, event: self };
+        env::log_str(&format!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&log).unwrap()));
+    }
+
+    pub(crate) fn config_changed(parameter: &str, value: impl ToString) {
+        WordchainEvent::ConfigChanged { parameter: parameter.to_string(), value: value.to_string() }.emit();
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+
+    use crate::wordchain::tests::{get_context, setup_active_tournament};
+
+    #[test]
+    fn test_events_logged() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_reveal_window(1000);
+
+        let logs = get_logs();
+        assert_eq!(
+            logs.last().unwrap(),
+            r#"EVENT_JSON:{"standard":"wordchain","version":"1.0.0","event":"config_changed","data":{"parameter":"reveal_window","value":"1000"}}"#
+        );
+    }
+
+    #[test]
+    fn test_activation_event_follows_last_join() {
+        let mut context = get_context(accounts(0));
+        setup_active_tournament(&mut context);
+
+        let logs = get_logs();
+        assert!(logs[logs.len() - 2].contains(r#""event":"player_joined""#));
+        assert_eq!(
+            logs.last().unwrap(),
+            r#"EVENT_JSON:{"standard":"wordchain","version":"1.0.0","event":"tournament_activated","data":{"tournament_id":"1"}}"#
+        );
+    }
+}
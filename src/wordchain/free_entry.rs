@@ -0,0 +1,168 @@
+use super::*;
+
+/// NEAR held for every entry into a free tournament to make throwaway accounts costly; returned when the player leaves or the tournament closes.
+pub const FREE_ENTRY_DEPOSIT: Balance = 10_000_000_000_000_000_000_000;
+
+impl Tournament {
+    /// Free tournaments take no stake; their prizes come entirely from sponsorships.
+    pub(crate) fn is_free(&self) -> bool {
+        self.minimum_stake == 0
+    }
+}
+
+fn allowlisted(eligibility_rules: &[EligibilityRule], account_id: &AccountId) -> bool {
+    eligibility_rules.iter().any(|rule| matches!(rule, EligibilityRule::ALLOWLIST(accounts) if accounts.contains(account_id)))
+}
+
+impl Wordchain {
+
+    /// Takes the stake an entry is joining with: the caller's whole staked balance, or nothing for a free tournament.
+    pub(crate) fn internal_take_entry_stake(&mut self, tournament: &Tournament, account_id: &AccountId) -> Balance {
+        if tournament.is_free() {
+            return 0;
+        }
+        let ft_stake = self.stakes.get(account_id).and_then(|stakes| stakes.get(&tournament.ft_address));
+        require!(ft_stake.is_some(), "No stake made");
+        let ft_stake = ft_stake.unwrap();
+        require!(ft_stake >= tournament.minimum_stake, "You must have staked at least the minimum stake before creating tournament");
+
+        self.internal_debit_stake(account_id, &tournament.ft_address, ft_stake);
+        ft_stake
+    }
+
+    /// Checks the NEAR attached to an entry: free tournaments, with a `minimum_stake` of 0, hold `FREE_ENTRY_DEPOSIT` unless the account is allowlisted.
+    pub(crate) fn internal_take_entry_deposit(&self, minimum_stake: Balance, eligibility_rules: &[EligibilityRule], account_id: &AccountId) -> Balance {
+        if minimum_stake > 0 || allowlisted(eligibility_rules, account_id) {
+            require!(env::attached_deposit() == 0, "This tournament does not take a deposit");
+            return 0;
+        }
+        require!(env::attached_deposit() == FREE_ENTRY_DEPOSIT, format!("Free tournaments require a deposit of {} yoctoNEAR", FREE_ENTRY_DEPOSIT));
+        FREE_ENTRY_DEPOSIT
+    }
+
+    /// Returns the stake and entry deposit a player still has in escrow.
+    pub(crate) fn internal_refund_entry(&mut self, tournament: &Tournament, player: &TournamentPlayer) {
+        if player.stake_amount > 0 {
+            self.internal_release_pool(&tournament.ft_address, player.stake_amount);
+            WordchainEvent::StakeWithdrawn {
+                account_id: player.account_id.clone(),
+                ft_address: tournament.ft_address.clone(),
+                amount: U128(player.stake_amount),
+            }.emit();
+            Self::internal_ft_transfer_or_restore(&tournament.ft_address, &player.account_id, player.stake_amount, TransferFallback::STAKE);
+        }
+        Self::internal_refund_entry_deposit(player);
+    }
+
+    pub(crate) fn internal_refund_entry_deposit(player: &TournamentPlayer) {
+        if player.entry_deposit > 0 {
+            Promise::new(player.account_id.clone()).transfer(player.entry_deposit);
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{create_tournament, ft, get_context, join, new_contract, play, reveal, TournamentSetup};
+
+    /// Creates free tournament 1 as the contract owner, who does not take part in it.
+    fn create_free_tournament(contract: &mut Wordchain, eligibility_rules: Option<Vec<EligibilityRule>>) {
+        contract.create_tournament("free".to_string(), "free".to_string(), "chain".to_string(), ft(), U128(0), Some(TournamentSettings {
+            eligibility_rules,
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn test_free_tournament_paid_by_sponsors() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_free_tournament(&mut contract, None);
+
+        for player in 1..5 {
+            testing_env!(context.predecessor_account_id(accounts(player)).attached_deposit(FREE_ENTRY_DEPOSIT).build());
+            contract.join_tournament(U128(1), "NG".to_string(), None, None);
+        }
+        testing_env!(context.predecessor_account_id(ft()).attached_deposit(0).build());
+        contract.ft_on_transfer(accounts(5), U128(1000), r#"{"SPONSOR":{"tournament_id":"1"}}"#.to_string());
+
+        for player in 1..5 {
+            play(&mut contract, &mut context, accounts(player), player as u8 * 10);
+        }
+        context.block_timestamp((DAY_TO_MS + 1) * 1_000_000);
+        for player in 1..5 {
+            reveal(&mut contract, &mut context, accounts(player), player as u8 * 10);
+        }
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((3 * DAY_TO_MS) * 1_000_000).build());
+        contract.distribute_rewards(U128(1));
+
+        assert_eq!(contract.get_player_profile(accounts(4)).winnings(&ft()), 500);
+        assert_eq!(contract.stake_payouts.get(&ft()), Some(0));
+    }
+
+    #[test]
+    fn test_allowlisted_players_join_free_without_deposit() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_free_tournament(&mut contract, Some(vec![EligibilityRule::ALLOWLIST(vec![accounts(1)])]));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.join_tournament(U128(1), "NG".to_string(), None, None);
+        assert_eq!(contract.internal_get_tournament(U128(1)).players.len(), 1);
+    }
+
+    #[test]
+    fn test_refunded_stake_restored_if_transfer_fails() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup::default());
+        join(&mut contract, &mut context, accounts(1));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.leave_tournament(U128(1));
+        let callback = get_created_receipts().into_iter()
+            .flat_map(|receipt| receipt.actions)
+            .find_map(|action| match action {
+                VmAction::FunctionCall { function_name, args, .. } if function_name == "on_transfer_resolved" => Some(String::from_utf8(args).unwrap()),
+                _ => None,
+            });
+        assert!(callback.unwrap().contains(r#""fallback":"STAKE""#));
+    }
+
+    #[test]
+    #[should_panic(expected = "Free tournaments require a deposit")]
+    fn test_free_tournament_requires_deposit() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_free_tournament(&mut contract, None);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.join_tournament(U128(1), "NG".to_string(), None, None);
+    }
+
+    #[test]
+    fn test_free_tournament_creator_pays_deposit() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        testing_env!(context.predecessor_account_id(accounts(1)).attached_deposit(FREE_ENTRY_DEPOSIT).build());
+        create_free_tournament(&mut contract, None);
+
+        let tournament = contract.internal_get_tournament(U128(1));
+        assert_eq!(tournament.players[0].account_id, accounts(1));
+        assert_eq!(tournament.players[0].entry_deposit, FREE_ENTRY_DEPOSIT);
+    }
+
+    #[test]
+    #[should_panic(expected = "Free tournaments require a deposit")]
+    fn test_free_tournament_creator_requires_deposit() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        create_free_tournament(&mut contract, None);
+    }
+}
@@ -0,0 +1,300 @@
+use super::*;
+
+/// Time between a proposal being created and it becoming executable, until the admins change it.
+pub const DEFAULT_PROPOSAL_DELAY_MS: u64 = DAY_TO_MS;
+
+/// Sensitive changes that, once admins are configured, can only be made through an approved proposal.
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalAction {
+    SET_STAKE_COMMISSION { value: u64 },
+    SET_COMMISSION_BOUNDS { min: u64, max: u64 },
+    SET_TOKEN_COMMISSION { ft_address: AccountId, commission: Option<u64> },
+    SET_GAME_TYPE_COMMISSION { game_type_id: String, commission: Option<u64> },
+    SET_MIN_PLAYERS { num: u8 },
+    WITHDRAW_VALUE { to: AccountId, ft_address: AccountId, amount: Option<U128> },
+    TRANSFER_OWNERSHIP { new_owner: AccountId },
+    SET_ADMINS { admins: Vec<AccountId>, threshold: u32, delay_ms: u64 },
+    SET_KEEPER_BOUNTY { value: u64 },
+    SET_REFERRAL_SHARE { value: u64 },
+    SET_MAX_CREATOR_FEE { value: u64 },
+    SET_SPONSORSHIP_COMMISSION { charged: bool },
+    SET_WITHDRAWAL_LIMIT { ft_address: AccountId, period_ms: u64, max_amount: Option<U128> },
+    SET_WITHDRAWAL_ALLOWLIST { destinations: Vec<AccountId> },
+    /// Deploys the staged code with this hash, once its timelock has passed as well.
+    DEPLOY_CODE { sha256: String },
+}
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Eq, PartialEq)]
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalStatus {
+    PENDING,
+    EXECUTED,
+    CANCELLED,
+}
+
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Proposal {
+    id: u64,
+    action: ProposalAction,
+    proposer: AccountId,
+    approvals: Vec<AccountId>,
+    created_at: u64,
+    executable_at: u64,
+    status: ProposalStatus,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AdminSettings {
+    admins: Vec<AccountId>,
+    threshold: u32,
+    delay_ms: u64,
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    /// Hands sensitive changes over to `threshold` of `admins`. Only the owner can do this, and only once;
+    /// afterwards the admin set is changed through a `SET_ADMINS` proposal.
+    pub fn set_admins(&mut self, admins: Vec<AccountId>, threshold: u32, delay_ms: u64) {
+        require!(self.owner == env::predecessor_account_id(), "Unauthorized");
+        require!(self.admins.is_empty(), "Admins can only be changed through a proposal");
+        self.internal_set_admins(admins, threshold, delay_ms);
+    }
+
+    pub fn get_admins(&self) -> AdminSettings {
+        AdminSettings { admins: self.admins.clone(), threshold: self.admin_threshold, delay_ms: self.proposal_delay }
+    }
+
+    /// Queues `action`, counting the proposer as its first approval.
+    pub fn propose(&mut self, action: ProposalAction) -> u64 {
+        self.internal_assert_admin();
+        let now = env::block_timestamp_ms();
+        let id = self.proposals.len();
+
+        self.proposals.push(&Proposal {
+            id,
+            action,
+            proposer: env::predecessor_account_id(),
+            approvals: vec![env::predecessor_account_id()],
+            created_at: now,
+            executable_at: now + self.proposal_delay,
+            status: ProposalStatus::PENDING,
+        });
+        WordchainEvent::ProposalCreated { id, proposer: env::predecessor_account_id() }.emit();
+        id
+    }
+
+    pub fn approve_proposal(&mut self, id: u64) {
+        self.internal_assert_admin();
+        let mut proposal = self.internal_get_pending_proposal(id);
+        require!(!proposal.approvals.contains(&env::predecessor_account_id()), "Proposal is already approved by the caller");
+
+        proposal.approvals.push(env::predecessor_account_id());
+        self.proposals.replace(id, &proposal);
+        WordchainEvent::ProposalApproved { id, account_id: env::predecessor_account_id() }.emit();
+    }
+
+    /// Any admin can cancel a pending proposal.
+    pub fn cancel_proposal(&mut self, id: u64) {
+        self.internal_assert_admin();
+        let mut proposal = self.internal_get_pending_proposal(id);
+
+        proposal.status = ProposalStatus::CANCELLED;
+        self.proposals.replace(id, &proposal);
+        WordchainEvent::ProposalCancelled { id }.emit();
+    }
+
+    /// Applies a proposal approved by enough current admins once its delay has passed.
+    pub fn execute_proposal(&mut self, id: u64) {
+        self.internal_assert_admin();
+        let mut proposal = self.internal_get_pending_proposal(id);
+        let approvals = proposal.approvals.iter().filter(|account_id| self.admins.contains(account_id)).count();
+        require!(approvals as u32 >= self.admin_threshold, "Proposal does not have enough approvals");
+        require!(env::block_timestamp_ms() >= proposal.executable_at, "Proposal delay has not passed");
+
+        proposal.status = ProposalStatus::EXECUTED;
+        self.proposals.replace(id, &proposal);
+
+        match proposal.action {
+            ProposalAction::SET_STAKE_COMMISSION { value } => self.internal_set_percentage_stake_commission(value),
+            ProposalAction::SET_COMMISSION_BOUNDS { min, max } => self.internal_set_commission_bounds(min, max),
+            ProposalAction::SET_TOKEN_COMMISSION { ft_address, commission } => self.internal_set_token_commission(ft_address, commission),
+            ProposalAction::SET_GAME_TYPE_COMMISSION { game_type_id, commission } => {
+                self.internal_set_game_type_commission(game_type_id, commission);
+            },
+            ProposalAction::SET_MIN_PLAYERS { num } => self.internal_set_min_players(num),
+            ProposalAction::WITHDRAW_VALUE { to, ft_address, amount } => {
+                self.internal_withdraw_value(to, ft_address, amount);
+            },
+            ProposalAction::TRANSFER_OWNERSHIP { new_owner } => self.internal_transfer_ownership(new_owner),
+            ProposalAction::SET_ADMINS { admins, threshold, delay_ms } => self.internal_set_admins(admins, threshold, delay_ms),
+            ProposalAction::SET_KEEPER_BOUNTY { value } => self.internal_set_keeper_bounty(value),
+            ProposalAction::SET_REFERRAL_SHARE { value } => self.internal_set_referral_share(value),
+            ProposalAction::SET_MAX_CREATOR_FEE { value } => self.internal_set_max_creator_fee(value),
+            ProposalAction::SET_SPONSORSHIP_COMMISSION { charged } => self.internal_set_sponsorship_commission(charged),
+            ProposalAction::SET_WITHDRAWAL_LIMIT { ft_address, period_ms, max_amount } => {
+                self.internal_set_withdrawal_limit(ft_address, period_ms, max_amount);
+            },
+            ProposalAction::SET_WITHDRAWAL_ALLOWLIST { destinations } => self.internal_set_withdrawal_allowlist(destinations),
+            ProposalAction::DEPLOY_CODE { sha256 } => {
+                self.internal_deploy_staged_code(Some(sha256));
+            },
+        }
+        WordchainEvent::ProposalExecuted { id }.emit();
+    }
+
+    pub fn get_proposal(&self, id: u64) -> Option<Proposal> {
+        self.proposals.get(id)
+    }
+
+    pub fn get_pending_proposals(&self, from_index: Option<u64>, limit: Option<u64>) -> Vec<Proposal> {
+        self.proposals.iter()
+            .filter(|proposal| proposal.status == ProposalStatus::PENDING)
+            .skip(from_index.unwrap_or_default() as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+}
+
+impl Wordchain {
+
+    /// Guards the owner's direct access to actions that move to proposals once admins are configured.
+    pub(crate) fn internal_assert_owner_action(&self) {
+        require!(self.owner == env::predecessor_account_id(), "Unauthorized");
+        require!(self.admins.is_empty(), "Action requires an approved proposal");
+    }
+
+    fn internal_assert_admin(&self) {
+        require!(self.admins.contains(&env::predecessor_account_id()), "Unauthorized");
+    }
+
+    fn internal_get_pending_proposal(&self, id: u64) -> Proposal {
+        let proposal = self.proposals.get(id).unwrap_or_else(|| env::panic_str("Proposal with provided ID does not exist"));
+        require!(proposal.status == ProposalStatus::PENDING, "Proposal is no longer pending");
+        proposal
+    }
+
+    fn internal_set_admins(&mut self, mut admins: Vec<AccountId>, threshold: u32, delay_ms: u64) {
+        admins.sort();
+        admins.dedup();
+        require!(threshold > 0 && threshold as usize <= admins.len(), "Threshold must be between 1 and the number of admins");
+
+        WordchainEvent::config_changed(
+            "admins",
+            format!("{}/{}:{}", threshold, admins.iter().map(|admin| admin.as_str()).collect::<Vec<&str>>().join("|"), delay_ms)
+        );
+        self.admins = admins;
+        self.admin_threshold = threshold;
+        self.proposal_delay = delay_ms;
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{ft, get_context, new_contract};
+
+    /// A contract governed by accounts 1, 2 and 3, two of which must approve.
+    fn governed_contract(context: &mut VMContextBuilder) -> Wordchain {
+        let mut contract = new_contract(context);
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_admins(vec![accounts(1), accounts(2), accounts(3)], 2, DEFAULT_PROPOSAL_DELAY_MS);
+        contract
+    }
+
+    #[test]
+    fn test_proposal_executes_after_approval_and_delay() {
+        let mut context = get_context(accounts(0));
+        let mut contract = governed_contract(&mut context);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        let id = contract.propose(ProposalAction::SET_STAKE_COMMISSION { value: 2000 });
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.approve_proposal(id);
+        assert_eq!(contract.get_pending_proposals(None, None).len(), 1);
+
+        testing_env!(context.block_timestamp(DEFAULT_PROPOSAL_DELAY_MS * 1_000_000).build());
+        contract.execute_proposal(id);
+        assert_eq!(contract.percentage_stake_commission, 2000);
+        assert!(contract.get_proposal(id).unwrap().status == ProposalStatus::EXECUTED);
+        assert!(contract.get_pending_proposals(None, None).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal delay has not passed")]
+    fn test_proposal_waits_for_delay() {
+        let mut context = get_context(accounts(0));
+        let mut contract = governed_contract(&mut context);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        let id = contract.propose(ProposalAction::SET_MIN_PLAYERS { num: 6 });
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.approve_proposal(id);
+        contract.execute_proposal(id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal does not have enough approvals")]
+    fn test_proposal_needs_threshold() {
+        let mut context = get_context(accounts(0));
+        let mut contract = governed_contract(&mut context);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp(0).build());
+        let id = contract.propose(ProposalAction::TRANSFER_OWNERSHIP { new_owner: accounts(4) });
+        testing_env!(context.block_timestamp(DEFAULT_PROPOSAL_DELAY_MS * 1_000_000).build());
+        contract.execute_proposal(id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal is no longer pending")]
+    fn test_cancelled_proposal_can_not_execute() {
+        let mut context = get_context(accounts(0));
+        let mut contract = governed_contract(&mut context);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        let id = contract.propose(ProposalAction::SET_MIN_PLAYERS { num: 6 });
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.cancel_proposal(id);
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.approve_proposal(id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Action requires an approved proposal")]
+    fn test_owner_can_not_bypass_admins() {
+        let mut context = get_context(accounts(0));
+        let mut contract = governed_contract(&mut context);
+        contract.set_percentage_stake_commission(2000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Action requires an approved proposal")]
+    fn test_owner_can_not_change_payout_shares_with_admins() {
+        let mut context = get_context(accounts(0));
+        let mut contract = governed_contract(&mut context);
+        contract.set_keeper_bounty(10000);
+    }
+
+    #[test]
+    fn test_withdrawal_limit_set_by_proposal() {
+        let mut context = get_context(accounts(0));
+        let mut contract = governed_contract(&mut context);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        let id = contract.propose(ProposalAction::SET_WITHDRAWAL_LIMIT { ft_address: ft(), period_ms: DAY_TO_MS, max_amount: Some(U128(500)) });
+        testing_env!(context.predecessor_account_id(accounts(2)).block_timestamp(DEFAULT_PROPOSAL_DELAY_MS * 1_000_000).build());
+        contract.approve_proposal(id);
+        contract.execute_proposal(id);
+        let limit = near_sdk::serde_json::to_value(contract.get_withdrawal_limit(ft())).unwrap();
+        assert_eq!(limit["max_amount"], "500");
+    }
+}
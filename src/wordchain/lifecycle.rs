@@ -0,0 +1,140 @@
+use super::*;
+
+#[near_bindgen]
+impl Wordchain {
+
+    /// Moves a tournament to its next state once it is due, callable by anyone:
+    /// PENDING_VOLUME is activated once play starts with enough players and refunded past the deadline,
+    /// ACTIVE past the deadline moves to REVEAL,
+    /// and a tournament past its reveal window is settled. A bracket is only settled once decided, whatever its deadlines.
+    /// Settling pays the caller `keeper_bounty` basis points of the commission.
+    pub fn advance_tournament(&mut self, tournament_id: U128) -> TournamentState {
+        let mut tournament = self.internal_get_tournament(tournament_id);
+        let now = env::block_timestamp_ms();
+
+        let due_for_reveal = tournament.status == TournamentState::ACTIVE
+            && tournament.format == TournamentFormat::LEADERBOARD
+            && tournament.tournament_deadline <= now
+            && tournament.reveal_deadline > now;
+
+        let due_for_activation = tournament.status == TournamentState::PENDING_VOLUME && tournament.tournament_deadline > now;
+
+        if due_for_activation {
+            self.internal_check_activation(&mut tournament);
+            require!(tournament.status == TournamentState::ACTIVE, "Tournament can not be advanced yet");
+        } else if due_for_reveal {
+            tournament.status = TournamentState::REVEAL;
+        } else {
+            let settling = tournament.status != TournamentState::PENDING_VOLUME;
+            let due = match tournament.status {
+                TournamentState::PENDING_VOLUME => tournament.tournament_deadline <= now,
+                TournamentState::ACTIVE | TournamentState::REVEAL if tournament.format == TournamentFormat::BRACKET => tournament.bracket_winner().is_some(),
+                TournamentState::ACTIVE | TournamentState::REVEAL => tournament.reveal_deadline <= now,
+                _ => false,
+            };
+            require!(due, "Tournament can not be advanced yet");
+
+            let commission = self.internal_close_tournament(&mut tournament);
+            if settling {
+                self.internal_pay_keeper_bounty(&tournament.ft_address, commission);
+            }
+        }
+
+        self.internal_save_tournament(&tournament);
+        tournament.status
+    }
+
+    pub fn set_keeper_bounty(&mut self, keeper_bounty: u64) {
+        self.internal_assert_owner_action();
+        self.internal_set_keeper_bounty(keeper_bounty);
+    }
+}
+
+impl Wordchain {
+
+    pub(crate) fn internal_set_keeper_bounty(&mut self, keeper_bounty: u64) {
+        require!(keeper_bounty <= 10000, "Keeper bounty can not exceed the commission");
+        self.keeper_bounty = keeper_bounty;
+        WordchainEvent::config_changed("keeper_bounty", keeper_bounty);
+    }
+
+    fn internal_pay_keeper_bounty(&mut self, ft_address: &AccountId, commission: Balance) {
+        let bounty = (self.keeper_bounty as u128 * commission) / 10000;
+        if bounty == 0 {
+            return;
+        }
+
+        self.internal_debit_keeper_bounty(ft_address, bounty);
+
+        Self::internal_ft_transfer_or_restore(ft_address, &env::predecessor_account_id(), bounty, TransferFallback::KEEPER_BOUNTY);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{create_tournament, ft, get_context, join, new_contract, setup_active_tournament, setup_tournament, TournamentSetup};
+
+    #[test]
+    fn test_advance_through_lifecycle() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+
+        testing_env!(context.predecessor_account_id(accounts(5)).block_timestamp(DAY_TO_MS * 1_000_000).build());
+        assert!(contract.advance_tournament(U128(1)) == TournamentState::REVEAL);
+
+        testing_env!(context.block_timestamp(2 * DAY_TO_MS * 1_000_000).build());
+        assert!(contract.advance_tournament(U128(1)) == TournamentState::CLOSED);
+
+        // 15% of the 4000 staked, less the 5% keeper bounty.
+        assert_eq!(contract.stake_payouts.get(&ft()), Some(570));
+    }
+
+    #[test]
+    fn test_failed_keeper_bounty_returns_to_treasury() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+        testing_env!(context.predecessor_account_id(accounts(5)).block_timestamp(DAY_TO_MS * 1_000_000).build());
+        contract.advance_tournament(U128(1));
+        testing_env!(context.block_timestamp(2 * DAY_TO_MS * 1_000_000).build());
+        contract.advance_tournament(U128(1));
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.on_transfer_resolved(ft(), accounts(5), U128(30), TransferFallback::KEEPER_BOUNTY, Err(PromiseError::Failed));
+        assert_eq!(contract.stake_payouts.get(&ft()), Some(600));
+    }
+
+    #[test]
+    fn test_advance_expires_pending_tournament() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup::default());
+        join(&mut contract, &mut context, accounts(1));
+
+        testing_env!(context.predecessor_account_id(accounts(5)).block_timestamp(DAY_TO_MS * 1_000_000).build());
+        assert!(contract.advance_tournament(U128(1)) == TournamentState::EXPIRED);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tournament can not be advanced yet")]
+    fn test_advance_before_deadline() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+
+        testing_env!(context.predecessor_account_id(accounts(5)).build());
+        contract.advance_tournament(U128(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Tournament can not be advanced yet")]
+    fn test_undecided_bracket_not_settled_past_deadline() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_tournament(&mut context, TournamentSetup { format: Some(TournamentFormat::BRACKET), ..Default::default() });
+
+        testing_env!(context.predecessor_account_id(accounts(5)).block_timestamp(3 * DAY_TO_MS * 1_000_000).build());
+        contract.advance_tournament(U128(1));
+    }
+}
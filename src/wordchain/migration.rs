@@ -0,0 +1,627 @@
+use super::*;
+use crate::upgrade::{self, StagedCode};
+
+/// Storage key holding the layout version of the contract state; state written before versioning has none and is V1.
+pub const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+pub const CURRENT_STATE_VERSION: u16 = 2;
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) enum TournamentStateV1 {
+    PENDING_VOLUME,
+    ACTIVE,
+    DELETED,
+    CLOSED,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct TournamentPlayerV1 {
+    id: U128,
+    account_id: AccountId,
+    stake_amount: Balance,
+    score: u64,
+    number_of_games_played: u64,
+    join_date: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct TournamentV1 {
+    id: U128,
+    name: String,
+    tournament_key: String,
+    game_type_id: String,
+    owner: AccountId,
+    minimum_stake: Balance,
+    created_at: u64,
+    total_stake: Balance,
+    country: String,
+    ft_address: AccountId,
+    tournament_deadline: u64,
+    tournament_type: TournamentType,
+    status: TournamentStateV1,
+    players: Vec<TournamentPlayerV1>,
+}
+
+/// The layout deployed before state versioning: single round tournaments with scores published in the clear.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct WordchainV1 {
+    paused: bool,
+    owner: AccountId,
+    pending_owner: AccountId,
+    min_tournament_players: u8,
+    stake_payouts: LookupMap<AccountId, Balance>,
+    percentage_stake_commission: u64,
+    game_types: Vector<GameType>,
+    tournaments_to_players: LookupMap<U128, Vector<AccountId>>,
+    tournaments: Vector<TournamentV1>,
+    supported_countries: Vector<String>,
+    stakes: LookupMap<AccountId, LookupMap<AccountId, Balance>>,
+}
+
+/// The layout written by state version 2, frozen so later fields on `Wordchain` do not change how it is read.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct WordchainV2 {
+    paused: bool,
+    owner: AccountId,
+    pending_owner: AccountId,
+    admins: Vec<AccountId>,
+    admin_threshold: u32,
+    proposal_delay: u64,
+    proposals: Vector<Proposal>,
+    min_tournament_players: u8,
+    max_tournament_players: u32,
+    stake_payouts: LookupMap<AccountId, Balance>,
+    treasury_totals: LookupMap<AccountId, TreasuryTotals>,
+    withdrawals: Vector<Withdrawal>,
+    withdrawal_limits: LookupMap<AccountId, WithdrawalLimit>,
+    withdrawal_allowlist: Vec<AccountId>,
+    liabilities: LookupMap<AccountId, Liabilities>,
+    percentage_stake_commission: u64,
+    min_commission: u64,
+    max_commission: u64,
+    token_commissions: LookupMap<AccountId, u64>,
+    game_type_commissions: LookupMap<String, u64>,
+    reveal_window: u64,
+    keeper_bounty: u64,
+    sponsorship_commission: bool,
+    max_creator_fee: u64,
+    creator_earnings: LookupMap<AccountId, HashMap<AccountId, Balance>>,
+    referral_share: u64,
+    referrers: LookupMap<AccountId, AccountId>,
+    referral_stats: LookupMap<AccountId, ReferralStats>,
+    game_types: Vector<GameType>,
+    tournaments_to_players: LookupMap<U128, Vector<AccountId>>,
+    tournaments: Vector<Tournament>,
+    supported_countries: UnorderedSet<String>,
+    country_tournaments: LookupMap<String, Vec<U128>>,
+    stakes: LookupMap<AccountId, LookupMap<AccountId, Balance>>,
+    legacy_stake_import: bool,
+    challenges: Vector<Challenge>,
+    player_profiles: LookupMap<AccountId, PlayerProfile>,
+    wct_token: Option<AccountId>,
+    templates: Vector<TournamentTemplate>,
+}
+
+/// A balance staked before the V1 migration, as recorded by the token transfers to the contract.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LegacyStake {
+    pub account_id: AccountId,
+    pub ft_address: AccountId,
+    pub amount: U128,
+}
+
+/// Only built once per migration, so the variants are kept inline.
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum VersionedWordchain {
+    V1(WordchainV1),
+    V2(WordchainV2),
+}
+
+impl VersionedWordchain {
+    pub(crate) fn read() -> Self {
+        let version = env::storage_read(STATE_VERSION_KEY)
+            .map_or(1, |bytes| u16::try_from_slice(&bytes).unwrap_or_else(|_| env::panic_str("Invalid state version")));
+
+        match version {
+            1 => VersionedWordchain::V1(env::state_read().unwrap_or_else(|| env::panic_str("Contract is not initialized"))),
+            2 => VersionedWordchain::V2(env::state_read().unwrap_or_else(|| env::panic_str("Contract is not initialized"))),
+            _ => env::panic_str("Unknown state version"),
+        }
+    }
+
+    pub(crate) fn write_version() {
+        env::storage_write(STATE_VERSION_KEY, &CURRENT_STATE_VERSION.try_to_vec().unwrap());
+    }
+
+    fn owner(&self) -> &AccountId {
+        match self {
+            VersionedWordchain::V1(state) => &state.owner,
+            VersionedWordchain::V2(state) => &state.owner,
+        }
+    }
+
+    fn has_admins(&self) -> bool {
+        match self {
+            VersionedWordchain::V1(_) => false,
+            VersionedWordchain::V2(state) => !state.admins.is_empty(),
+        }
+    }
+
+    fn into_current(self) -> Wordchain {
+        match self {
+            VersionedWordchain::V1(state) => state.into(),
+            VersionedWordchain::V2(state) => state.into(),
+        }
+    }
+}
+
+impl From<WordchainV2> for Wordchain {
+    fn from(state: WordchainV2) -> Self {
+        Wordchain {
+            paused: state.paused,
+            owner: state.owner,
+            pending_owner: state.pending_owner,
+            admins: state.admins,
+            admin_threshold: state.admin_threshold,
+            proposal_delay: state.proposal_delay,
+            proposals: state.proposals,
+            min_tournament_players: state.min_tournament_players,
+            max_tournament_players: state.max_tournament_players,
+            stake_payouts: state.stake_payouts,
+            treasury_totals: state.treasury_totals,
+            withdrawals: state.withdrawals,
+            withdrawal_limits: state.withdrawal_limits,
+            withdrawal_allowlist: state.withdrawal_allowlist,
+            liabilities: state.liabilities,
+            percentage_stake_commission: state.percentage_stake_commission,
+            min_commission: state.min_commission,
+            max_commission: state.max_commission,
+            token_commissions: state.token_commissions,
+            game_type_commissions: state.game_type_commissions,
+            reveal_window: state.reveal_window,
+            keeper_bounty: state.keeper_bounty,
+            sponsorship_commission: state.sponsorship_commission,
+            max_creator_fee: state.max_creator_fee,
+            creator_earnings: state.creator_earnings,
+            referral_share: state.referral_share,
+            referrers: state.referrers,
+            referral_stats: state.referral_stats,
+            game_types: state.game_types,
+            tournaments_to_players: state.tournaments_to_players,
+            tournaments: state.tournaments,
+            supported_countries: state.supported_countries,
+            country_tournaments: state.country_tournaments,
+            stakes: state.stakes,
+            legacy_stake_import: state.legacy_stake_import,
+            challenges: state.challenges,
+            player_profiles: state.player_profiles,
+            wct_token: state.wct_token,
+            templates: state.templates,
+        }
+    }
+}
+
+impl From<WordchainV1> for Wordchain {
+    fn from(state: WordchainV1) -> Self {
+        let mut contract = Wordchain {
+            paused: state.paused,
+            owner: state.owner,
+            pending_owner: state.pending_owner,
+            admins: Vec::new(),
+            admin_threshold: 0,
+            proposal_delay: DEFAULT_PROPOSAL_DELAY_MS,
+            proposals: Vector::new(b"a"),
+            min_tournament_players: state.min_tournament_players,
+            max_tournament_players: DEFAULT_MAX_TOURNAMENT_PLAYERS,
+            stake_payouts: state.stake_payouts,
+            treasury_totals: LookupMap::new(b"x"),
+            withdrawals: Vector::new(b"h"),
+            withdrawal_limits: LookupMap::new(b"l"),
+            withdrawal_allowlist: Vec::new(),
+            liabilities: LookupMap::new(b"y"),
+            percentage_stake_commission: state.percentage_stake_commission,
+            min_commission: DEFAULT_MIN_COMMISSION.min(state.percentage_stake_commission),
+            max_commission: DEFAULT_MAX_COMMISSION.max(state.percentage_stake_commission),
+            token_commissions: LookupMap::new(b"k"),
+            game_type_commissions: LookupMap::new(b"n"),
+            reveal_window: DEFAULT_REVEAL_WINDOW_MS,
+            keeper_bounty: DEFAULT_KEEPER_BOUNTY,
+            sponsorship_commission: false,
+            max_creator_fee: DEFAULT_MAX_CREATOR_FEE,
+            creator_earnings: LookupMap::new(b"e"),
+            referral_share: DEFAULT_REFERRAL_SHARE,
+            referrers: LookupMap::new(b"f"),
+            referral_stats: LookupMap::new(b"q"),
+            game_types: state.game_types,
+            tournaments_to_players: state.tournaments_to_players,
+            tournaments: Vector::new(b"t"),
+            supported_countries: UnorderedSet::new(b"u"),
+            country_tournaments: LookupMap::new(b"i"),
+            // V1 created every account's balances under the same `b"g"` prefix, so they all read and wrote one shared map.
+            // Balances start over under a new outer map and are imported per account with `import_legacy_stakes`.
+            stakes: LookupMap::new(b"b"),
+            legacy_stake_import: true,
+            challenges: Vector::new(b"w"),
+            player_profiles: LookupMap::new(b"r"),
+            wct_token: None,
+            templates: Vector::new(b"m"),
+        };
+
+        // Codes that are not valid ISO 3166-1 alpha-2 codes are dropped along with the old list.
+        let mut countries = state.supported_countries;
+        countries.iter().filter_map(|country| country::normalize_country(&country)).for_each(|code| {
+            contract.supported_countries.insert(&code);
+        });
+        countries.clear();
+
+        // Tournaments are rewritten in place under the same prefix, so every old entry is read before the first write.
+        let tournaments = state.tournaments.to_vec();
+        for tournament in tournaments {
+            let tournament = contract.internal_migrate_tournament(tournament);
+            if matches!(tournament.status, TournamentState::PENDING_VOLUME | TournamentState::ACTIVE) {
+                contract.internal_lock_pool(&tournament.ft_address, tournament.total_stake);
+                contract.internal_index_country_tournament(&tournament);
+            }
+            contract.tournaments.push(&tournament);
+        }
+        contract
+    }
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    /// Upgrades the stored state to the current layout after new code is deployed, callable by the contract itself
+    /// or, while no admins are configured, by the owner.
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = VersionedWordchain::read();
+        require!(
+            (state.owner() == &env::predecessor_account_id() && !state.has_admins()) || env::predecessor_account_id() == env::current_account_id(),
+            "Unauthorized"
+        );
+
+        VersionedWordchain::write_version();
+        state.into_current()
+    }
+
+    pub fn get_state_version(&self) -> u16 {
+        CURRENT_STATE_VERSION
+    }
+
+    /// Stages the wasm passed as the raw call input, deployable once `UPGRADE_TIMELOCK_MS` has passed.
+    /// Only the owner stages code; with admins configured, deploying it still takes a `DEPLOY_CODE` proposal for its hash.
+    pub fn stage_code(&mut self) -> StagedCode {
+        require!(env::predecessor_account_id() == self.owner, "Unauthorized");
+        let staged = upgrade::stage_code();
+        WordchainEvent::CodeStaged { sha256: staged.sha256.clone(), deployable_at: staged.deployable_at }.emit();
+        staged
+    }
+
+    pub fn get_staged_code(&self) -> Option<StagedCode> {
+        upgrade::staged_code()
+    }
+
+    pub fn cancel_staged_code(&mut self) {
+        require!(env::predecessor_account_id() == self.owner, "Unauthorized");
+        upgrade::cancel_staged_code();
+    }
+
+    /// Credits balances staked before the V1 migration, each under its own account's map, which also counts them as liabilities.
+    pub fn import_legacy_stakes(&mut self, stakes: Vec<LegacyStake>) {
+        self.internal_assert_owner_action();
+        require!(self.legacy_stake_import, "No legacy stakes to import");
+        stakes.into_iter().for_each(|stake| self.internal_credit_stake(&stake.account_id, &stake.ft_address, stake.amount.0));
+    }
+
+    /// Closes the import of V1 balances for good.
+    pub fn finish_legacy_stake_import(&mut self) {
+        self.internal_assert_owner_action();
+        require!(self.legacy_stake_import, "No legacy stakes to import");
+        self.legacy_stake_import = false;
+    }
+
+    /// Deploys the staged code and migrates the state to it. Once admins are configured this takes a `DEPLOY_CODE` proposal.
+    pub fn deploy_staged_code(&mut self) -> Promise {
+        self.internal_assert_owner_action();
+        self.internal_deploy_staged_code(None)
+    }
+}
+
+impl Wordchain {
+
+    /// Deploys the staged code, which must hash to `sha256` when a proposal approved that exact code.
+    pub(crate) fn internal_deploy_staged_code(&mut self, sha256: Option<String>) -> Promise {
+        let staged = upgrade::staged_code().map(|staged| staged.sha256).unwrap_or_default();
+        if let Some(sha256) = sha256 {
+            require!(sha256 == staged, "Staged code does not match the proposal");
+        }
+        let promise = upgrade::deploy_staged_code();
+        WordchainEvent::CodeDeployed { sha256: staged }.emit();
+        promise
+    }
+
+    /// A V1 tournament becomes a single round leaderboard whose published scores count for that round.
+    fn internal_migrate_tournament(&self, tournament: TournamentV1) -> Tournament {
+        let reveal_deadline = tournament.tournament_deadline + self.reveal_window;
+        // V1 never added joined stakes to `total_stake`, so the pool is rebuilt from what every player staked.
+        let total_stake = tournament.players.iter().map(|player| player.stake_amount).sum();
+        let players = tournament.players.into_iter().map(|player| {
+            let mut migrated = TournamentPlayer::new(player.id, player.account_id, player.stake_amount);
+            migrated.score = player.score;
+            migrated.number_of_games_played = player.number_of_games_played;
+            migrated.join_date = player.join_date;
+            migrated.round_scores = vec![RoundScore { score: player.score, number_of_games_played: player.number_of_games_played }];
+            migrated
+        }).collect::<Vec<TournamentPlayer>>();
+
+        Tournament {
+            id: tournament.id,
+            name: tournament.name,
+            tournament_key: tournament.tournament_key,
+            game_type_id: tournament.game_type_id,
+            owner: tournament.owner,
+            minimum_stake: tournament.minimum_stake,
+            created_at: tournament.created_at,
+            registration: RegistrationWindow::new(tournament.created_at, tournament.tournament_deadline),
+            total_stake,
+            country: country::normalize_country(&tournament.country).unwrap_or(tournament.country),
+            ft_address: tournament.ft_address,
+            tournament_deadline: tournament.tournament_deadline,
+            reveal_deadline,
+            tournament_type: tournament.tournament_type,
+            format: TournamentFormat::LEADERBOARD,
+            bracket_seeding: BracketSeeding::RANDOM,
+            status: match tournament.status {
+                TournamentStateV1::PENDING_VOLUME => TournamentState::PENDING_VOLUME,
+                TournamentStateV1::ACTIVE => TournamentState::ACTIVE,
+                TournamentStateV1::DELETED => TournamentState::DELETED,
+                TournamentStateV1::CLOSED => TournamentState::CLOSED,
+            },
+            challenge_seed: None,
+            rounds: vec![TournamentRound {
+                round: 0,
+                start: tournament.created_at,
+                end: tournament.tournament_deadline,
+                reveal_deadline,
+                eliminations: 0,
+            }],
+            rounds_settled: 0,
+            bracket: Vec::new(),
+            team_settings: None,
+            teams: Vec::new(),
+            eligibility_rules: Vec::new(),
+            min_players: self.min_tournament_players as u32,
+            max_players: self.max_tournament_players.max(players.len() as u32),
+            prize_curve: DEFAULT_PRIZE_CURVE.to_vec(),
+            commission: self.percentage_stake_commission,
+            creator_fee: 0,
+            sponsorships: Vec::new(),
+            waitlist: Vec::new(),
+            players,
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{ft, get_context, new_contract};
+
+    fn write_v1_state() {
+        let mut game_types = Vector::new(b"g");
+        game_types.push(&GameType { identifier: "chain".to_string(), max_score: 100 });
+        let mut supported_countries = Vector::new(b"c");
+        supported_countries.push(&"NG".to_string());
+        supported_countries.push(&"NG".to_string());
+        supported_countries.push(&"Nigeria".to_string());
+
+        let mut tournaments = Vector::new(b"t");
+        tournaments.push(&TournamentV1 {
+            id: U128(1),
+            name: "daily".to_string(),
+            tournament_key: "key".to_string(),
+            game_type_id: "chain".to_string(),
+            owner: accounts(0),
+            minimum_stake: 1000,
+            created_at: 0,
+            // V1 left the total at 0 however many players joined.
+            total_stake: 0,
+            country: String::new(),
+            ft_address: ft(),
+            tournament_deadline: DAY_TO_MS,
+            tournament_type: TournamentType::PUBLIC,
+            status: TournamentStateV1::ACTIVE,
+            players: (1..3).map(|player| TournamentPlayerV1 {
+                id: U128(player as u128),
+                account_id: accounts(player),
+                stake_amount: 1000,
+                score: player as u64 * 10,
+                number_of_games_played: 1,
+                join_date: 0,
+            }).collect(),
+        });
+
+        // Both stakers' balances land in the one map every V1 account shared.
+        let mut stakes = LookupMap::new(b"s");
+        let mut legacy_stakes = LookupMap::new(b"g");
+        legacy_stakes.insert(&ft(), &1500);
+        stakes.insert(&accounts(3), &legacy_stakes);
+        stakes.insert(&accounts(4), &LookupMap::new(b"g"));
+
+        env::state_write(&WordchainV1 {
+            paused: false,
+            owner: accounts(0),
+            pending_owner: accounts(0),
+            min_tournament_players: 4,
+            stake_payouts: LookupMap::new(b"d"),
+            percentage_stake_commission: 1500,
+            game_types,
+            tournaments_to_players: LookupMap::new(b"p"),
+            tournaments,
+            supported_countries,
+            stakes,
+        });
+    }
+
+    #[test]
+    fn test_migrate_from_v1() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        write_v1_state();
+
+        let contract = Wordchain::migrate();
+        assert_eq!(contract.get_state_version(), CURRENT_STATE_VERSION);
+        env::state_write(&contract);
+        assert!(matches!(VersionedWordchain::read(), VersionedWordchain::V2(_)));
+
+        let tournament = contract.internal_get_tournament(U128(1));
+        assert!(tournament.status == TournamentState::ACTIVE);
+        assert_eq!(tournament.reveal_deadline, 2 * DAY_TO_MS);
+        assert_eq!(tournament.standings()[0].account_id, accounts(2));
+        assert_eq!(tournament.total_stake, 2000);
+        let liabilities = near_sdk::serde_json::to_value(contract.get_liabilities(ft())).unwrap();
+        assert_eq!(liabilities["pools"], "2000");
+        assert!(contract.check_supported_country("NG".to_string()));
+        assert_eq!(contract.get_supported_countries(None, None), vec!["NG"]);
+        assert_eq!(contract.get_gametypes(None).len(), 1);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        let preview = near_sdk::serde_json::to_value(contract.preview_settlement(U128(1))).unwrap();
+        assert_eq!(preview["net_pool"], "1700");
+    }
+
+    #[test]
+    fn test_legacy_stakes_imported_per_account() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        write_v1_state();
+
+        let mut contract = Wordchain::migrate();
+        assert!(contract.stakes.get(&accounts(3)).is_none());
+        contract.import_legacy_stakes(vec![
+            LegacyStake { account_id: accounts(3), ft_address: ft(), amount: U128(1000) },
+            LegacyStake { account_id: accounts(4), ft_address: ft(), amount: U128(500) },
+        ]);
+        contract.finish_legacy_stake_import();
+
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.create_challenge("chain".to_string(), ft(), U128(1000), None, None);
+        testing_env!(context.predecessor_account_id(accounts(4)).build());
+        contract.create_challenge("chain".to_string(), ft(), U128(200), None, None);
+
+        assert_eq!(contract.stakes.get(&accounts(3)).unwrap().get(&ft()), Some(0));
+        assert_eq!(contract.stakes.get(&accounts(4)).unwrap().get(&ft()), Some(300));
+        let liabilities = near_sdk::serde_json::to_value(contract.get_liabilities(ft())).unwrap();
+        assert_eq!(liabilities["stakes"], "300");
+        assert_eq!(liabilities["pools"], "3200");
+    }
+
+    #[test]
+    #[should_panic(expected = "No legacy stakes to import")]
+    fn test_legacy_import_closed_on_new_state() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        contract.import_legacy_stakes(Vec::new());
+    }
+
+    #[test]
+    fn test_migrate_current_state_is_unchanged() {
+        let mut context = get_context(accounts(0));
+        let contract = new_contract(&mut context);
+        env::state_write(&contract);
+
+        let migrated = Wordchain::migrate();
+        assert_eq!(migrated.get_gametypes(None).len(), 1);
+        assert_eq!(migrated.percentage_stake_commission, 1500);
+    }
+
+    #[test]
+    fn test_stage_and_deploy_code() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        context.context.input = b"\0asm".to_vec();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+
+        let staged = contract.stage_code();
+        assert_eq!(staged.size, 4);
+        assert_eq!(staged.deployable_at, upgrade::UPGRADE_TIMELOCK_MS);
+        assert_eq!(contract.get_staged_code().unwrap().sha256, staged.sha256);
+
+        testing_env!(context.block_timestamp(upgrade::UPGRADE_TIMELOCK_MS * 1_000_000).build());
+        contract.deploy_staged_code();
+        assert!(contract.get_staged_code().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Staged code is still timelocked")]
+    fn test_deploy_waits_for_timelock() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        context.context.input = b"\0asm".to_vec();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+
+        contract.stage_code();
+        testing_env!(context.block_timestamp((upgrade::UPGRADE_TIMELOCK_MS - 1) * 1_000_000).build());
+        contract.deploy_staged_code();
+    }
+
+    #[test]
+    fn test_deploy_through_proposal() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        context.context.input = b"\0asm".to_vec();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        let staged = contract.stage_code();
+        contract.set_admins(vec![accounts(1), accounts(2)], 2, DEFAULT_PROPOSAL_DELAY_MS);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        let id = contract.propose(ProposalAction::DEPLOY_CODE { sha256: staged.sha256 });
+        testing_env!(context.predecessor_account_id(accounts(2)).block_timestamp(upgrade::UPGRADE_TIMELOCK_MS * 1_000_000).build());
+        contract.approve_proposal(id);
+        contract.execute_proposal(id);
+        assert!(contract.get_staged_code().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Action requires an approved proposal")]
+    fn test_owner_can_not_deploy_with_admins() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        context.context.input = b"\0asm".to_vec();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.stage_code();
+        contract.set_admins(vec![accounts(1), accounts(2)], 2, DEFAULT_PROPOSAL_DELAY_MS);
+
+        testing_env!(context.block_timestamp(upgrade::UPGRADE_TIMELOCK_MS * 1_000_000).build());
+        contract.deploy_staged_code();
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn test_owner_can_not_migrate_with_admins() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        contract.set_admins(vec![accounts(1), accounts(2)], 2, DEFAULT_PROPOSAL_DELAY_MS);
+        env::state_write(&contract);
+        VersionedWordchain::write_version();
+
+        testing_env!(context.current_account_id(accounts(5)).build());
+        Wordchain::migrate();
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn test_migrate_requires_owner() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        write_v1_state();
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        Wordchain::migrate();
+    }
+}
@@ -0,0 +1,149 @@
+use super::*;
+
+/// Highest creator fee, in basis points of the pool, until the owner changes it.
+pub const DEFAULT_MAX_CREATOR_FEE: u64 = 500;
+
+/// How a tournament's pool is divided when it settles.
+pub(crate) struct PayoutSplit {
+    pub(crate) platform_commission: Balance,
+    pub(crate) creator_fee: Balance,
+    pub(crate) referral_rewards: Vec<(AccountId, Balance)>,
+    /// Stakes and sponsorships left for prizes, before any guarantee top up.
+    pub(crate) prize_pool: Balance,
+    pub(crate) guarantee_top_up: Balance,
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    pub fn get_creator_earnings(&self, account_id: AccountId) -> HashMap<AccountId, U128> {
+        self.creator_earnings.get(&account_id).unwrap_or_default()
+            .into_iter()
+            .map(|(ft_address, amount)| (ft_address, U128(amount)))
+            .collect()
+    }
+
+    /// Transfers the caller's creator fees in `ft_address` to them.
+    pub fn claim_creator_earnings(&mut self, ft_address: AccountId) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let mut earnings = self.creator_earnings.get(&account_id).unwrap_or_default();
+        let amount = earnings.remove(&ft_address).unwrap_or_default();
+        require!(amount > 0, "No creator earnings to claim");
+
+        self.creator_earnings.insert(&account_id, &earnings);
+        self.internal_track_claimable(&ft_address, 0, amount);
+        Self::internal_ft_transfer_or_restore(&ft_address, &account_id, amount, TransferFallback::CREATOR_EARNINGS);
+        U128(amount)
+    }
+
+    pub fn set_max_creator_fee(&mut self, max_creator_fee: u64) {
+        self.internal_assert_owner_action();
+        self.internal_set_max_creator_fee(max_creator_fee);
+    }
+}
+
+impl Wordchain {
+
+    pub(crate) fn internal_set_max_creator_fee(&mut self, max_creator_fee: u64) {
+        require!(max_creator_fee <= 10000, "Creator fee can not exceed 10000 basis points");
+        self.max_creator_fee = max_creator_fee;
+        WordchainEvent::config_changed("max_creator_fee", max_creator_fee);
+    }
+
+    /// The creator's fee and the referral rewards come out of the platform commission, so they never exceed it.
+    pub(crate) fn internal_payout_split(&self, tournament: &Tournament) -> PayoutSplit {
+        let sponsored = tournament.sponsored_pool();
+        let commissionable = tournament.total_stake + if self.sponsorship_commission { sponsored } else { 0 };
+        let commission = (tournament.commission as u128 * commissionable) / 10000;
+        let creator_fee = (tournament.creator_fee.min(tournament.commission) as u128 * commissionable) / 10000;
+        let prize_pool = tournament.total_stake + sponsored - commission;
+
+        let referral_rewards = self.internal_referral_rewards(tournament, commission - creator_fee);
+        let referral_total = referral_rewards.iter().map(|(_, reward)| reward).sum::<Balance>();
+
+        PayoutSplit {
+            platform_commission: commission - creator_fee - referral_total,
+            creator_fee,
+            referral_rewards,
+            prize_pool,
+            guarantee_top_up: tournament.guaranteed_pool().saturating_sub(prize_pool),
+        }
+    }
+
+    pub(crate) fn internal_credit_creator(&mut self, account_id: &AccountId, ft_address: &AccountId, amount: Balance) {
+        if amount == 0 {
+            return;
+        }
+        let mut earnings = self.creator_earnings.get(account_id).unwrap_or_default();
+        *earnings.entry(ft_address.clone()).or_default() += amount;
+        self.creator_earnings.insert(account_id, &earnings);
+        self.internal_track_claimable(ft_address, amount, 0);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{ft, get_context, join, new_contract, stake};
+
+    /// A tournament created by player 1 with a `creator_fee` and joined by three more players.
+    fn create_with_creator_fee(contract: &mut Wordchain, context: &mut near_sdk::test_utils::VMContextBuilder, creator_fee: u64) {
+        stake(contract, context, accounts(1));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.create_tournament("daily".to_string(), "key".to_string(), "chain".to_string(), ft(), U128(1000), Some(TournamentSettings {
+            creator_fee: Some(creator_fee),
+            ..Default::default()
+        }));
+        for player in 2..5 {
+            join(contract, context, accounts(player));
+        }
+    }
+
+    #[test]
+    fn test_creator_fee_split_from_commission() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_with_creator_fee(&mut contract, &mut context, 500);
+
+        let preview = near_sdk::serde_json::to_value(contract.preview_settlement(U128(1))).unwrap();
+        assert_eq!(preview["platform_commission"], "400");
+        assert_eq!(preview["creator_fee"], "200");
+        assert_eq!(preview["net_pool"], "3400");
+
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((3 * DAY_TO_MS) * 1_000_000).build());
+        contract.distribute_rewards(U128(1));
+        assert_eq!(contract.stake_payouts.get(&ft()), Some(400));
+        assert_eq!(contract.get_creator_earnings(accounts(1)).get(&ft()), Some(&U128(200)));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        assert_eq!(contract.claim_creator_earnings(ft()), U128(200));
+        assert!(contract.get_creator_earnings(accounts(1)).is_empty());
+    }
+
+    #[test]
+    fn test_failed_claim_restores_creator_earnings() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_with_creator_fee(&mut contract, &mut context, 500);
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((3 * DAY_TO_MS) * 1_000_000).build());
+        contract.distribute_rewards(U128(1));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.claim_creator_earnings(ft());
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.on_transfer_resolved(ft(), accounts(1), U128(200), TransferFallback::CREATOR_EARNINGS, Err(PromiseError::Failed));
+        assert_eq!(contract.get_creator_earnings(accounts(1)).get(&ft()), Some(&U128(200)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Creator fee is above the platform maximum")]
+    fn test_creator_fee_capped() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        contract.set_max_creator_fee(100);
+        create_with_creator_fee(&mut contract, &mut context, 500);
+    }
+}
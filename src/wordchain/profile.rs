@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use super::*;
+
+pub const DEFAULT_RATING: u32 = 1200;
+const MIN_RATING: i64 = 100;
+const RATING_K_FACTOR: i64 = 32;
+/// Players on each side of a placement a player is rated against, which keeps settlement linear in the number of players.
+const RATING_NEIGHBOURS: usize = 4;
+
+/// Expected score in per mille of a player rated 0, 50, 100, ... points above the opponent.
+const EXPECTED_SCORE_TABLE: [i64; 17] = [500, 571, 640, 703, 760, 808, 849, 882, 909, 930, 947, 960, 969, 977, 983, 987, 990];
+
+#[derive(Clone, Default, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PlayerProfile {
+    ratings: HashMap<String, u32>,
+    tournaments_played: u64,
+    wins: u64,
+    winnings: HashMap<AccountId, U128>,
+}
+
+impl PlayerProfile {
+    pub(crate) fn rating(&self, game_type_id: &str) -> u32 {
+        self.ratings.get(game_type_id).copied().unwrap_or(DEFAULT_RATING)
+    }
+
+    pub(crate) fn tournaments_played(&self) -> u64 {
+        self.tournaments_played
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_rating(game_type_id: &str, rating: u32) -> Self {
+        Self { ratings: HashMap::from([(game_type_id.to_string(), rating)]), ..Default::default() }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn winnings(&self, ft_address: &AccountId) -> Balance {
+        self.winnings.get(ft_address).map_or(0, |winnings| winnings.0)
+    }
+}
+
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RatingRange {
+    min: Option<u32>,
+    max: Option<u32>,
+}
+
+impl RatingRange {
+    pub(crate) fn contains(&self, rating: u32) -> bool {
+        self.min.is_none_or(|min| rating >= min) && self.max.is_none_or(|max| rating <= max)
+    }
+}
+
+/// Integer Elo expectation in per mille, interpolated from `EXPECTED_SCORE_TABLE`.
+fn expected_score(rating: u32, opponent_rating: u32) -> i64 {
+    let difference = rating as i64 - opponent_rating as i64;
+    let distance = difference.abs().min(800);
+    let (step, offset) = ((distance / 50) as usize, distance % 50);
+
+    let upper = EXPECTED_SCORE_TABLE[(step + 1).min(16)];
+    let expected = EXPECTED_SCORE_TABLE[step] + (upper - EXPECTED_SCORE_TABLE[step]) * offset / 50;
+
+    if difference >= 0 { expected } else { 1000 - expected }
+}
+
+/// New ratings after a tournament where `placements` lists groups of tied accounts from first to last.
+/// Each player counts a game against up to `RATING_NEIGHBOURS` players placed either side, scaled down by the number of opponents.
+pub(crate) fn placement_ratings(placements: &[(AccountId, usize, u32)]) -> Vec<(AccountId, u32)> {
+    placements.iter().enumerate().map(|(index, (account_id, placement, rating))| {
+        let window = index.saturating_sub(RATING_NEIGHBOURS)..(index + RATING_NEIGHBOURS + 1).min(placements.len());
+        let opponents = window.len().saturating_sub(1).max(1) as i64;
+
+        let delta = window.filter(|other| *other != index)
+            .map(|other| &placements[other])
+            .map(|(_, other_placement, other_rating)| {
+                let actual = match placement.cmp(other_placement) {
+                    std::cmp::Ordering::Less => 1000,
+                    std::cmp::Ordering::Equal => 500,
+                    std::cmp::Ordering::Greater => 0,
+                };
+                actual - expected_score(*rating, *other_rating)
+            })
+            .sum::<i64>();
+
+        let rating = (*rating as i64 + RATING_K_FACTOR * delta / 1000 / opponents).max(MIN_RATING);
+        (account_id.clone(), rating as u32)
+    }).collect()
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    pub fn get_player_profile(&self, account_id: AccountId) -> PlayerProfile {
+        self.player_profiles.get(&account_id).unwrap_or_default()
+    }
+}
+
+impl Wordchain {
+
+    pub(crate) fn internal_player_rating(&self, account_id: &AccountId, game_type_id: &str) -> u32 {
+        self.player_profiles.get(account_id).map_or(DEFAULT_RATING, |profile| profile.rating(game_type_id))
+    }
+
+    /// Records a closed tournament on every player profile: placement based rating, games and wins.
+    pub(crate) fn internal_record_tournament_results(&mut self, tournament: &Tournament, placements: &[Vec<(AccountId, Balance)>]) {
+        let ranked = placements.iter().enumerate()
+            .flat_map(|(placement, accounts)| accounts.iter().map(move |(account_id, _)| (account_id.clone(), placement)))
+            .map(|(account_id, placement)| {
+                let rating = self.internal_player_rating(&account_id, &tournament.game_type_id);
+                (account_id, placement, rating)
+            })
+            .collect::<Vec<(AccountId, usize, u32)>>();
+        let ratings = placement_ratings(&ranked).into_iter().collect::<HashMap<AccountId, u32>>();
+
+        for player in tournament.players.iter() {
+            let mut profile = self.get_player_profile(player.account_id.clone());
+            profile.tournaments_played += 1;
+
+            if let Some(rating) = ratings.get(&player.account_id) {
+                profile.ratings.insert(tournament.game_type_id.clone(), *rating);
+            }
+            if placements.first().is_some_and(|winners| winners.iter().any(|(account_id, _)| account_id == &player.account_id)) {
+                profile.wins += 1;
+            }
+            self.player_profiles.insert(&player.account_id, &profile);
+        }
+    }
+
+    pub(crate) fn internal_record_winnings(&mut self, account_id: &AccountId, ft_address: &AccountId, amount: Balance) {
+        let mut profile = self.get_player_profile(account_id.clone());
+        let winnings = profile.winnings.entry(ft_address.clone()).or_insert(U128(0));
+        winnings.0 += amount;
+        self.player_profiles.insert(account_id, &profile);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{create_tournament, ft, get_context, join, new_contract, play, reveal, setup_active_tournament, TournamentSetup};
+
+    #[test]
+    fn test_expected_score_is_symmetric() {
+        assert_eq!(expected_score(1200, 1200), 500);
+        assert_eq!(expected_score(1300, 1200), 640);
+        assert_eq!(expected_score(1200, 1300), 360);
+        assert_eq!(expected_score(2500, 1200), 990);
+    }
+
+    #[test]
+    fn test_placement_ratings() {
+        let ratings = placement_ratings(&[
+            (accounts(1), 0, 1200),
+            (accounts(2), 1, 1200),
+            (accounts(3), 2, 1200),
+        ]);
+
+        assert_eq!(ratings[0], (accounts(1), 1216));
+        assert_eq!(ratings[1], (accounts(2), 1200));
+        assert_eq!(ratings[2], (accounts(3), 1184));
+    }
+
+    #[test]
+    fn test_placement_ratings_only_count_neighbours() {
+        let placements: Vec<(AccountId, usize, u32)> = (0..20).map(|placement| (format!("player{}.near", placement).parse().unwrap(), placement, DEFAULT_RATING)).collect();
+        let ratings = placement_ratings(&placements);
+
+        // The winner beats its four neighbours, a middle player beats four and loses to four.
+        assert_eq!(ratings[0].1, DEFAULT_RATING + 16);
+        assert_eq!(ratings[10].1, DEFAULT_RATING);
+        assert_eq!(ratings[19].1, DEFAULT_RATING - 16);
+    }
+
+    #[test]
+    fn test_profiles_updated_on_close() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+
+        for player in 1..5 {
+            play(&mut contract, &mut context, accounts(player), player as u8 * 10);
+        }
+        context.block_timestamp((DAY_TO_MS + 1) * 1_000_000);
+        for player in 1..5 {
+            reveal(&mut contract, &mut context, accounts(player), player as u8 * 10);
+        }
+
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((3 * DAY_TO_MS) * 1_000_000).build());
+        contract.distribute_rewards(U128(1));
+
+        let winner = contract.get_player_profile(accounts(4));
+        assert_eq!(winner.tournaments_played, 1);
+        assert_eq!(winner.wins, 1);
+        assert!(winner.rating("chain") > DEFAULT_RATING);
+        assert_eq!(winner.winnings.get(&ft()), Some(&U128(1700)));
+
+        let last = contract.get_player_profile(accounts(1));
+        assert_eq!(last.wins, 0);
+        assert!(last.rating("chain") < DEFAULT_RATING);
+    }
+
+    #[test]
+    #[should_panic(expected = "Player rating 1200 is outside the tournament rating range")]
+    fn test_rating_range_enforced_on_join() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup {
+            eligibility_rules: Some(vec![EligibilityRule::RATING_RANGE(RatingRange { min: Some(1300), max: None })]),
+            ..Default::default()
+        });
+
+        join(&mut contract, &mut context, accounts(1));
+    }
+}
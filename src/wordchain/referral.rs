@@ -0,0 +1,150 @@
+use super::*;
+
+/// Share of the platform commission, in basis points, paid to the referrer of each staked entry.
+pub const DEFAULT_REFERRAL_SHARE: u64 = 1000;
+
+#[derive(Clone, Default, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReferralStats {
+    referrals: u64,
+    earnings: HashMap<AccountId, U128>,
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    pub fn get_referrer(&self, account_id: AccountId) -> Option<AccountId> {
+        self.referrers.get(&account_id)
+    }
+
+    pub fn get_referral_stats(&self, account_id: AccountId) -> ReferralStats {
+        self.referral_stats.get(&account_id).unwrap_or_default()
+    }
+
+    /// Transfers the caller's referral earnings in `ft_address` to them.
+    pub fn claim_referral_earnings(&mut self, ft_address: AccountId) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let mut stats = self.referral_stats.get(&account_id).unwrap_or_default();
+        let amount = stats.earnings.remove(&ft_address).unwrap_or(U128(0));
+        require!(amount.0 > 0, "No referral earnings to claim");
+
+        self.referral_stats.insert(&account_id, &stats);
+        self.internal_track_claimable(&ft_address, 0, amount.0);
+        Self::internal_ft_transfer_or_restore(&ft_address, &account_id, amount.0, TransferFallback::REFERRAL_EARNINGS);
+        amount
+    }
+
+    pub fn set_referral_share(&mut self, referral_share: u64) {
+        self.internal_assert_owner_action();
+        self.internal_set_referral_share(referral_share);
+    }
+}
+
+impl Wordchain {
+
+    pub(crate) fn internal_set_referral_share(&mut self, referral_share: u64) {
+        require!(referral_share <= 10000, "Referral share can not exceed 10000 basis points");
+        self.referral_share = referral_share;
+        WordchainEvent::config_changed("referral_share", referral_share);
+    }
+
+    /// Records who referred `account_id`; only the first referrer of an account is kept.
+    pub(crate) fn internal_record_referrer(&mut self, account_id: &AccountId, referrer: Option<AccountId>) {
+        let referrer = match referrer {
+            Some(referrer) if &referrer != account_id && self.referrers.get(account_id).is_none() => referrer,
+            _ => return,
+        };
+
+        let mut stats = self.referral_stats.get(&referrer).unwrap_or_default();
+        stats.referrals += 1;
+        self.referral_stats.insert(&referrer, &stats);
+        self.referrers.insert(account_id, &referrer);
+    }
+
+    /// The referrers of a tournament's players with their share of `platform_commission`, in proportion to each referee's stake.
+    pub(crate) fn internal_referral_rewards(&self, tournament: &Tournament, platform_commission: Balance) -> Vec<(AccountId, Balance)> {
+        if tournament.total_stake == 0 {
+            return Vec::new();
+        }
+        tournament.players.iter()
+            .filter_map(|player| self.referrers.get(&player.account_id).map(|referrer| {
+                let reward = platform_commission * self.referral_share as u128 * player.stake_amount / (10000 * tournament.total_stake);
+                (referrer, reward)
+            }))
+            .filter(|(_, reward)| *reward > 0)
+            .collect()
+    }
+
+    pub(crate) fn internal_credit_referrer(&mut self, referrer: &AccountId, ft_address: &AccountId, amount: Balance) {
+        let mut stats = self.referral_stats.get(referrer).unwrap_or_default();
+        stats.earnings.entry(ft_address.clone()).or_insert(U128(0)).0 += amount;
+        self.referral_stats.insert(referrer, &stats);
+        self.internal_track_claimable(ft_address, amount, 0);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{create_tournament, ft, get_context, join, new_contract, TournamentSetup};
+
+    #[test]
+    fn test_referrer_recorded_once_and_rewarded() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup::default());
+
+        // Player 1 is referred through the staking message, player 2 when joining.
+        testing_env!(context.predecessor_account_id(ft()).build());
+        contract.ft_on_transfer(accounts(1), U128(1000), format!(r#"{{"STAKE":{{"referrer":"{}"}}}}"#, accounts(5)));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.join_tournament(U128(1), "NG".to_string(), None, Some(accounts(3)));
+
+        testing_env!(context.predecessor_account_id(ft()).build());
+        contract.ft_on_transfer(accounts(2), U128(1000), String::new());
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.join_tournament(U128(1), "NG".to_string(), None, Some(accounts(5)));
+
+        for player in 3..5 {
+            join(&mut contract, &mut context, accounts(player));
+        }
+        assert_eq!(contract.get_referrer(accounts(1)), Some(accounts(5)));
+        assert_eq!(contract.get_referral_stats(accounts(5)).referrals, 2);
+
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((3 * DAY_TO_MS) * 1_000_000).build());
+        contract.distribute_rewards(U128(1));
+
+        // 10% of the platform's 600 for each of the two referees holding a quarter of the stake.
+        assert_eq!(contract.get_referral_stats(accounts(5)).earnings.get(&ft()), Some(&U128(30)));
+        assert_eq!(contract.stake_payouts.get(&ft()), Some(570));
+
+        testing_env!(context.predecessor_account_id(accounts(5)).build());
+        assert_eq!(contract.claim_referral_earnings(ft()), U128(30));
+    }
+
+    #[test]
+    fn test_failed_claim_restores_referral_earnings() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        contract.internal_credit_referrer(&accounts(5), &ft(), 30);
+
+        testing_env!(context.predecessor_account_id(accounts(5)).build());
+        contract.claim_referral_earnings(ft());
+        assert!(contract.get_referral_stats(accounts(5)).earnings.is_empty());
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.on_transfer_resolved(ft(), accounts(5), U128(30), TransferFallback::REFERRAL_EARNINGS, Err(PromiseError::Failed));
+        assert_eq!(contract.get_referral_stats(accounts(5)).earnings.get(&ft()), Some(&U128(30)));
+    }
+
+    #[test]
+    fn test_self_referral_ignored() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        contract.internal_record_referrer(&accounts(1), Some(accounts(1)));
+        assert_eq!(contract.get_referrer(accounts(1)), None);
+    }
+}
@@ -0,0 +1,252 @@
+use super::*;
+
+pub const MIN_REGISTRATION_WINDOW_MS: u64 = 60 * 60 * 1000;
+pub const MIN_ROUND_DURATION_MS: u64 = 60 * 60 * 1000;
+
+/// A point in time given either as Unix milliseconds or as an ISO 8601 date time such as `2024-05-01T18:00:00Z`.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde", untagged)]
+pub enum TimeInput {
+    MS(u64),
+    ISO8601(String),
+}
+
+impl TimeInput {
+    pub(crate) fn to_ms(&self) -> u64 {
+        match self {
+            TimeInput::MS(ms) => *ms,
+            TimeInput::ISO8601(value) => {
+                let datetime = iso8601::datetime(value);
+                require!(datetime.is_ok(), format!("Invalid ISO 8601 date time: {}", value));
+                let ms = datetime_to_ms(&datetime.unwrap());
+                require!(ms >= 0, "Date time must be after 1970-01-01");
+                ms as u64
+            },
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ScheduleInput {
+    registration_open: Option<TimeInput>,
+    registration_close: Option<TimeInput>,
+    play_start: Option<TimeInput>,
+    play_end: Option<TimeInput>,
+}
+
+impl ScheduleInput {
+    pub(crate) fn starting_at(registration_open: u64, play_start: u64) -> Self {
+        Self {
+            registration_open: Some(TimeInput::MS(registration_open)),
+            play_start: Some(TimeInput::MS(play_start)),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RegistrationWindow {
+    opens: u64,
+    closes: u64,
+}
+
+impl RegistrationWindow {
+    pub(crate) fn new(opens: u64, closes: u64) -> Self {
+        Self { opens, closes }
+    }
+
+    pub(crate) fn failure(&self, timestamp: u64) -> Option<&'static str> {
+        if timestamp < self.opens {
+            Some("Tournament registration has not opened yet")
+        } else if timestamp >= self.closes {
+            Some("Tournament registration is closed")
+        } else {
+            None
+        }
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    let year = year as i64 - if month <= 2 { 1 } else { 0 };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn datetime_to_ms(datetime: &iso8601::DateTime) -> i64 {
+    let days = match datetime.date {
+        iso8601::Date::YMD { year, month, day } => days_from_civil(year, month, day),
+        iso8601::Date::Ordinal { year, ddd } => days_from_civil(year, 1, 1) + ddd as i64 - 1,
+        iso8601::Date::Week { year, ww, d } => {
+            // Week 1 is the week holding January 4th, weeks start on Monday.
+            let january_fourth = days_from_civil(year, 1, 4);
+            let week_one_monday = january_fourth - (january_fourth + 3).rem_euclid(7);
+            week_one_monday + (ww as i64 - 1) * 7 + d as i64 - 1
+        },
+    };
+    let time = &datetime.time;
+    let offset = time.tz_offset_hours as i64 * 3600 + time.tz_offset_minutes as i64 * 60;
+    let seconds = days * 86400 + time.hour as i64 * 3600 + time.minute as i64 * 60 + time.second as i64 - offset;
+
+    seconds * 1000 + time.millisecond as i64
+}
+
+impl Wordchain {
+
+    /// Resolves the registration window and the round schedule, defaulting to registration and play
+    /// starting now with rounds of `round_duration` until the last round ends.
+    pub(crate) fn internal_tournament_schedule(
+        &self,
+        schedule: ScheduleInput,
+        round_duration: u64,
+        rounds: u32,
+        eliminations_per_round: u32,
+    ) -> (RegistrationWindow, Vec<TournamentRound>) {
+        let now = env::block_timestamp_ms();
+        let play_start = schedule.play_start.map_or(now, |time| time.to_ms());
+        require!(play_start >= now, "Play can not start in the past");
+        let reveal_windows = (rounds as u64 - 1).checked_mul(self.reveal_window);
+        let play_with_reveals = reveal_windows.and_then(|reveal_windows| play_start.checked_add(reveal_windows));
+        require!(play_with_reveals.is_some(), "Tournament schedule is too long");
+        let reveal_windows = reveal_windows.unwrap();
+
+        let round_duration = match schedule.play_end {
+            Some(play_end) => {
+                let play_end = play_end.to_ms();
+                require!(play_end > play_with_reveals.unwrap(), "Play must end after it starts");
+                (play_end - play_start - reveal_windows) / rounds as u64
+            },
+            None => round_duration,
+        };
+        require!(round_duration >= MIN_ROUND_DURATION_MS, "Rounds must last at least one hour");
+
+        let rounds = self.internal_round_schedule(play_start, round_duration, rounds, eliminations_per_round);
+        let play_end = rounds.last().unwrap().end;
+
+        let registration = RegistrationWindow {
+            opens: schedule.registration_open.map_or(now, |time| time.to_ms()),
+            closes: schedule.registration_close.map_or(play_end, |time| time.to_ms()),
+        };
+        require!(registration.opens <= play_start, "Registration must open before play starts");
+        require!(registration.closes <= play_end, "Registration must close before play ends");
+        require!(registration.closes > now, "Registration must close in the future");
+        require!(registration.closes >= registration.opens + MIN_REGISTRATION_WINDOW_MS, "Registration must stay open for at least one hour");
+
+        (registration, rounds)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{create_tournament, ft, get_context, join, new_contract, TournamentSetup};
+
+    const HOUR_TO_MS: u64 = 60 * 60 * 1000;
+
+    #[test]
+    fn test_iso8601_to_ms() {
+        assert_eq!(TimeInput::ISO8601("1970-01-02T00:00:00Z".to_string()).to_ms(), DAY_TO_MS);
+        assert_eq!(TimeInput::ISO8601("2024-02-29T12:30:00.250Z".to_string()).to_ms(), 1_709_209_800_250);
+        assert_eq!(TimeInput::ISO8601("2024-02-29T14:30:00.250+02:00".to_string()).to_ms(), 1_709_209_800_250);
+        assert_eq!(TimeInput::ISO8601("2024-W09-4T12:30:00.250Z".to_string()).to_ms(), 1_709_209_800_250);
+        assert_eq!(TimeInput::ISO8601("2024-060T12:30:00.250Z".to_string()).to_ms(), 1_709_209_800_250);
+    }
+
+    #[test]
+    fn test_scheduled_start_waits_for_play_start() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup {
+            max_players: Some(4),
+            schedule: Some(ScheduleInput {
+                play_start: Some(TimeInput::MS(2 * HOUR_TO_MS)),
+                play_end: Some(TimeInput::ISO8601("1970-01-01T05:00:00Z".to_string())),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        for player in 1..6 {
+            join(&mut contract, &mut context, accounts(player));
+        }
+
+        let tournament = contract.internal_get_tournament(U128(1));
+        assert!(tournament.status == TournamentState::PENDING_VOLUME);
+        assert_eq!(tournament.rounds[0].end, 5 * HOUR_TO_MS);
+
+        // A seat freed before play starts goes to the waitlist.
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.leave_tournament(U128(1));
+        assert!(contract.get_waitlist(U128(1)).is_empty());
+
+        testing_env!(context.predecessor_account_id(accounts(5)).block_timestamp(2 * HOUR_TO_MS * 1_000_000).build());
+        assert!(contract.advance_tournament(U128(1)) == TournamentState::ACTIVE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tournament registration is closed")]
+    fn test_join_after_registration_closes() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup {
+            schedule: Some(ScheduleInput {
+                registration_close: Some(TimeInput::MS(HOUR_TO_MS)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        testing_env!(context.block_timestamp(HOUR_TO_MS * 1_000_000).build());
+        join(&mut contract, &mut context, accounts(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Play can not start in the past")]
+    fn test_play_start_in_the_past_rejected() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.block_timestamp(3 * HOUR_TO_MS * 1_000_000).build());
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup {
+            schedule: Some(ScheduleInput {
+                play_start: Some(TimeInput::MS(2 * HOUR_TO_MS)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Round interval is too long")]
+    fn test_round_interval_overflow_rejected() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        contract.create_tournament("daily".to_string(), "key".to_string(), "chain".to_string(), ft(), U128(1000), Some(TournamentSettings {
+            interval: Some(u64::MAX),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Registration must open before play starts")]
+    fn test_schedule_ordering_validated() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup {
+            schedule: Some(ScheduleInput {
+                registration_open: Some(TimeInput::MS(3 * HOUR_TO_MS)),
+                play_start: Some(TimeInput::MS(2 * HOUR_TO_MS)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+    }
+}
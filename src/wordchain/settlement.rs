@@ -0,0 +1,158 @@
+use super::*;
+use super::payout::PayoutSplit;
+
+/// Everything a tournament settlement pays out, computed before any transfer is made.
+pub(crate) struct SettlementPlan {
+    pub(crate) split: PayoutSplit,
+    pub(crate) placements: Vec<Vec<(AccountId, Balance)>>,
+    pub(crate) guarantee_refunds: Vec<(AccountId, Balance)>,
+    pub(crate) prize_pool: Balance,
+    /// Rank, starting at 1, with the account and the prize it is paid.
+    pub(crate) payouts: Vec<(u32, AccountId, Balance)>,
+    /// Part of the prize pool no placement is paid, when there are fewer placements than prizes or from rounding.
+    pub(crate) unallocated: Balance,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RankPayout {
+    rank: u32,
+    account_id: AccountId,
+    amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SettlementPreview {
+    status: TournamentState,
+    commission: U128,
+    platform_commission: U128,
+    creator_fee: U128,
+    referral_rewards: U128,
+    net_pool: U128,
+    payouts: Vec<RankPayout>,
+    /// Credited to the treasury along with the platform commission.
+    unallocated: U128,
+}
+
+impl Tournament {
+    /// Drops unrevealed commits, which never count towards the standing, and settles every round due at `timestamp`.
+    pub(crate) fn prepare_settlement(&mut self, timestamp: u64) {
+        self.players.iter_mut().for_each(|player| player.score_commits.clear());
+        self.settle_rounds(timestamp);
+    }
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    /// Dry run of settling the tournament now, built by the same plan `distribute_rewards` and `advance_tournament` execute.
+    /// A pending tournament would be refunded, so it previews as EXPIRED with nothing paid.
+    pub fn preview_settlement(&self, tournament_id: U128) -> SettlementPreview {
+        let mut tournament = self.internal_get_tournament(tournament_id);
+
+        if matches!(tournament.status, TournamentState::PENDING_VOLUME | TournamentState::DELETED) {
+            return SettlementPreview {
+                status: if tournament.status == TournamentState::DELETED { TournamentState::DELETED } else { TournamentState::EXPIRED },
+                commission: U128(0),
+                platform_commission: U128(0),
+                creator_fee: U128(0),
+                referral_rewards: U128(0),
+                net_pool: U128(0),
+                payouts: Vec::new(),
+                unallocated: U128(0),
+            };
+        }
+
+        tournament.prepare_settlement(env::block_timestamp_ms());
+        let plan = self.internal_settlement_plan(&tournament);
+        let referral_rewards = plan.split.referral_rewards.iter().map(|(_, reward)| reward).sum::<Balance>();
+
+        SettlementPreview {
+            status: TournamentState::CLOSED,
+            commission: U128(plan.split.platform_commission + plan.split.creator_fee + referral_rewards),
+            platform_commission: U128(plan.split.platform_commission),
+            creator_fee: U128(plan.split.creator_fee),
+            referral_rewards: U128(referral_rewards),
+            net_pool: U128(plan.prize_pool),
+            payouts: plan.payouts.into_iter()
+                .map(|(rank, account_id, amount)| RankPayout { rank, account_id, amount: U128(amount) })
+                .collect(),
+            unallocated: U128(plan.unallocated),
+        }
+    }
+}
+
+impl Wordchain {
+
+    /// Prizes are basis points of the prize pool; accounts sharing a placement split its prize by weight.
+    pub(crate) fn internal_settlement_plan(&self, tournament: &Tournament) -> SettlementPlan {
+        let split = self.internal_payout_split(tournament);
+        let (top_up, guarantee_refunds) = tournament.guarantee_usage(split.guarantee_top_up);
+        let prize_pool = split.prize_pool + top_up;
+        let placements = tournament.placements();
+
+        let mut payouts = Vec::new();
+        for (rank, (prize, accounts)) in tournament.prize_curve.iter().zip(placements.iter()).enumerate() {
+            let placement_prize = (*prize as u128 * prize_pool) / 10000;
+            let total_weight = accounts.iter().map(|(_, weight)| weight).sum::<Balance>();
+
+            accounts.iter().filter(|(_, weight)| *weight > 0).for_each(|(account_id, weight)| {
+                payouts.push((rank as u32 + 1, account_id.clone(), placement_prize * weight / total_weight));
+            });
+        }
+
+        let unallocated = prize_pool - payouts.iter().map(|(_, _, amount)| amount).sum::<Balance>();
+        SettlementPlan { split, placements, guarantee_refunds, prize_pool, payouts, unallocated }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{create_tournament, ft, get_context, new_contract, play, reveal, setup_active_tournament, TournamentSetup};
+
+    #[test]
+    fn test_preview_matches_settlement() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+
+        for player in 1..5 {
+            play(&mut contract, &mut context, accounts(player), player as u8 * 10);
+        }
+        context.block_timestamp((DAY_TO_MS + 1) * 1_000_000);
+        for player in 1..5 {
+            reveal(&mut contract, &mut context, accounts(player), player as u8 * 10);
+        }
+
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((3 * DAY_TO_MS) * 1_000_000).build());
+        let preview = contract.preview_settlement(U128(1));
+        assert!(preview.status == TournamentState::CLOSED);
+        assert_eq!(preview.commission, U128(600));
+        assert_eq!(preview.net_pool, U128(3400));
+        assert_eq!(
+            preview.payouts.iter().map(|payout| (payout.rank, payout.account_id.clone(), payout.amount)).collect::<Vec<_>>(),
+            vec![(1, accounts(4), U128(1700)), (2, accounts(3), U128(1156)), (3, accounts(2), U128(544))]
+        );
+
+        contract.distribute_rewards(U128(1));
+        for payout in preview.payouts {
+            assert_eq!(contract.get_player_profile(payout.account_id).winnings(&ft()), payout.amount.0);
+        }
+        assert_eq!(contract.stake_payouts.get(&ft()), Some(preview.platform_commission.0));
+    }
+
+    #[test]
+    fn test_preview_pending_tournament() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup::default());
+
+        let preview = contract.preview_settlement(U128(1));
+        assert!(preview.status == TournamentState::EXPIRED);
+        assert!(preview.payouts.is_empty());
+    }
+}
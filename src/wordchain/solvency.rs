@@ -0,0 +1,172 @@
+use super::*;
+
+/// Everything the contract owes in a token, kept up to date as tokens move between stakes, pools and claimable balances.
+#[derive(Clone, Default, BorshDeserialize, BorshSerialize)]
+pub struct Liabilities {
+    /// Deposited stakes not yet committed to a tournament or challenge.
+    stakes: Balance,
+    /// Stakes and sponsorships held by tournaments and challenges that are not settled yet.
+    pools: Balance,
+    /// Creator and referral earnings waiting to be claimed.
+    claimable: Balance,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LiabilitiesReport {
+    stakes: U128,
+    pools: U128,
+    claimable: U128,
+    treasury: U128,
+    total: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SolvencyReport {
+    ft_address: AccountId,
+    balance: U128,
+    liabilities: U128,
+    surplus: U128,
+    deficit: U128,
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    pub fn get_liabilities(&self, ft_address: AccountId) -> LiabilitiesReport {
+        let liabilities = self.liabilities.get(&ft_address).unwrap_or_default();
+        let treasury = self.stake_payouts.get(&ft_address).unwrap_or_default();
+
+        LiabilitiesReport {
+            stakes: U128(liabilities.stakes),
+            pools: U128(liabilities.pools),
+            claimable: U128(liabilities.claimable),
+            treasury: U128(treasury),
+            total: U128(liabilities.stakes + liabilities.pools + liabilities.claimable + treasury),
+        }
+    }
+
+    /// Compares the contract's balance of `ft_address` with its liabilities, logging a `solvency_checked` event.
+    pub fn reconcile(&mut self, ft_address: AccountId) -> Promise {
+        require!(self.owner == env::predecessor_account_id(), "Unauthorized");
+
+        ext_token_contract::ext(ft_address.clone())
+            .with_static_gas(GAS_FOR_FT_BALANCE_OF)
+            .ft_balance_of(env::current_account_id())
+            .then(Self::ext(env::current_account_id()).with_static_gas(GAS_FOR_RECONCILE_CALLBACK).on_reconcile(ft_address))
+    }
+
+    #[private]
+    pub fn on_reconcile(&self, ft_address: AccountId, #[callback_result] balance: Result<U128, PromiseError>) -> SolvencyReport {
+        require!(balance.is_ok(), "Could not fetch the token balance");
+        let balance = balance.unwrap().0;
+        let liabilities = self.get_liabilities(ft_address.clone()).total.0;
+
+        let report = SolvencyReport {
+            ft_address: ft_address.clone(),
+            balance: U128(balance),
+            liabilities: U128(liabilities),
+            surplus: U128(balance.saturating_sub(liabilities)),
+            deficit: U128(liabilities.saturating_sub(balance)),
+        };
+        WordchainEvent::SolvencyChecked {
+            ft_address,
+            balance: report.balance,
+            liabilities: report.liabilities,
+            surplus: report.surplus,
+            deficit: report.deficit,
+        }.emit();
+        report
+    }
+}
+
+impl Wordchain {
+
+    fn internal_update_liabilities(&mut self, ft_address: &AccountId, update: impl FnOnce(&mut Liabilities)) {
+        let mut liabilities = self.liabilities.get(ft_address).unwrap_or_default();
+        update(&mut liabilities);
+        self.liabilities.insert(ft_address, &liabilities);
+    }
+
+    pub(crate) fn internal_track_stakes(&mut self, ft_address: &AccountId, credited: Balance, debited: Balance) {
+        self.internal_update_liabilities(ft_address, |liabilities| liabilities.stakes = Self::internal_settle_liability(ft_address, "stakes", liabilities.stakes, credited, debited));
+    }
+
+    pub(crate) fn internal_lock_pool(&mut self, ft_address: &AccountId, amount: Balance) {
+        self.internal_update_liabilities(ft_address, |liabilities| liabilities.pools += amount);
+    }
+
+    pub(crate) fn internal_release_pool(&mut self, ft_address: &AccountId, amount: Balance) {
+        self.internal_update_liabilities(ft_address, |liabilities| liabilities.pools = Self::internal_settle_liability(ft_address, "pools", liabilities.pools, 0, amount));
+    }
+
+    pub(crate) fn internal_track_claimable(&mut self, ft_address: &AccountId, credited: Balance, claimed: Balance) {
+        self.internal_update_liabilities(ft_address, |liabilities| liabilities.claimable = Self::internal_settle_liability(ft_address, "claimable", liabilities.claimable, credited, claimed));
+    }
+
+    /// A liability never goes below zero. One that would means tokens left without ever being tracked, such as state
+    /// migrated from an untracked version, so it stops at zero and logs the deficit rather than blocking the payout.
+    fn internal_settle_liability(ft_address: &AccountId, liability: &str, balance: Balance, credited: Balance, debited: Balance) -> Balance {
+        let balance = balance + credited;
+        if debited > balance {
+            WordchainEvent::LiabilityDeficit {
+                ft_address: ft_address.clone(),
+                liability: liability.to_string(),
+                deficit: U128(debited - balance),
+            }.emit();
+        }
+        balance.saturating_sub(debited)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{ft, get_context, setup_active_tournament};
+
+    #[test]
+    fn test_liabilities_follow_tokens() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+
+        // The owner's stake stays deposited, the four players' stakes are in the pool.
+        let liabilities = contract.get_liabilities(ft());
+        assert_eq!(liabilities.stakes, U128(1000));
+        assert_eq!(liabilities.pools, U128(4000));
+
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((3 * DAY_TO_MS) * 1_000_000).build());
+        contract.distribute_rewards(U128(1));
+
+        // 3400 of prizes left the contract, the 600 commission went to the treasury.
+        let liabilities = contract.get_liabilities(ft());
+        assert_eq!(liabilities.pools, U128(0));
+        assert_eq!(liabilities.treasury, U128(600));
+        assert_eq!(liabilities.total, U128(1600));
+    }
+
+    #[test]
+    fn test_untracked_release_stops_at_zero() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+        contract.internal_release_pool(&ft(), 4001);
+
+        assert_eq!(contract.get_liabilities(ft()).pools, U128(0));
+        assert!(get_logs().last().unwrap().contains(r#""event":"liability_deficit","data":{"ft_address":"usdc.near","liability":"pools","deficit":"1"}"#));
+    }
+
+    #[test]
+    fn test_reconcile_reports_deficit() {
+        let mut context = get_context(accounts(0));
+        let contract = setup_active_tournament(&mut context);
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        let report = contract.on_reconcile(ft(), Ok(U128(4500)));
+        assert_eq!(report.liabilities, U128(5000));
+        assert_eq!(report.deficit, U128(500));
+        assert_eq!(report.surplus, U128(0));
+    }
+}
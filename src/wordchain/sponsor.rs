@@ -0,0 +1,158 @@
+use super::*;
+
+/// Tokens a sponsor added to a tournament's prize pool. A guarantee only tops the pool up to its amount
+/// and the unused part is returned to the sponsor at settlement.
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Sponsorship {
+    account_id: AccountId,
+    amount: U128,
+    guarantee: bool,
+}
+
+impl Tournament {
+    pub(crate) fn sponsored_pool(&self) -> Balance {
+        self.sponsorships.iter().filter(|sponsorship| !sponsorship.guarantee).map(|sponsorship| sponsorship.amount.0).sum()
+    }
+
+    pub(crate) fn guaranteed_pool(&self) -> Balance {
+        self.sponsorships.iter().filter(|sponsorship| sponsorship.guarantee).map(|sponsorship| sponsorship.amount.0).sum()
+    }
+
+    /// Covers `shortfall` of the prize pool from the guarantees, sharing the cost across guarantors by their deposit.
+    /// Returns the top up and what is left of each guarantee to refund.
+    pub(crate) fn guarantee_usage(&self, shortfall: Balance) -> (Balance, Vec<(AccountId, Balance)>) {
+        let guaranteed = self.guaranteed_pool();
+        let mut top_up = 0;
+
+        let refunds = self.sponsorships.iter().filter(|sponsorship| sponsorship.guarantee).filter_map(|sponsorship| {
+            let used = shortfall * sponsorship.amount.0 / guaranteed;
+            top_up += used;
+            (sponsorship.amount.0 > used).then(|| (sponsorship.account_id.clone(), sponsorship.amount.0 - used))
+        }).collect();
+
+        (top_up, refunds)
+    }
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    pub fn get_sponsorships(&self, tournament_id: U128) -> Vec<Sponsorship> {
+        self.internal_get_tournament(tournament_id).sponsorships
+    }
+
+    /// Whether sponsored tokens are charged the stake commission along with the player stakes.
+    pub fn set_sponsorship_commission(&mut self, charged: bool) {
+        self.internal_assert_owner_action();
+        self.internal_set_sponsorship_commission(charged);
+    }
+}
+
+impl Wordchain {
+
+    pub(crate) fn internal_set_sponsorship_commission(&mut self, charged: bool) {
+        self.sponsorship_commission = charged;
+        WordchainEvent::config_changed("sponsorship_commission", charged);
+    }
+
+    pub(crate) fn internal_sponsor_tournament(&mut self, tournament_id: U128, sponsor: AccountId, ft_address: &AccountId, amount: Balance, guarantee: bool) {
+        let mut tournament = self.internal_get_tournament(tournament_id);
+        require!(&tournament.ft_address == ft_address, "Tournament is played for another token");
+        require!(
+            matches!(tournament.status, TournamentState::PENDING_VOLUME | TournamentState::ACTIVE),
+            "Tournament can no longer be sponsored"
+        );
+
+        tournament.sponsorships.push(Sponsorship { account_id: sponsor, amount: U128(amount), guarantee });
+        self.internal_save_tournament(&tournament);
+        self.internal_lock_pool(ft_address, amount);
+    }
+
+    /// Returns every sponsorship of a tournament that is cancelled before it is played.
+    pub(crate) fn internal_refund_sponsors(&mut self, tournament: &mut Tournament) {
+        let sponsorships = std::mem::take(&mut tournament.sponsorships);
+        sponsorships.iter().for_each(|sponsorship| {
+            self.internal_release_pool(&tournament.ft_address, sponsorship.amount.0);
+            Self::internal_ft_transfer_or_restore(&tournament.ft_address, &sponsorship.account_id, sponsorship.amount.0, TransferFallback::STAKE);
+        });
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{create_tournament, ft, get_context, join, new_contract, play, reveal, setup_active_tournament, TournamentSetup};
+
+    fn sponsor(contract: &mut Wordchain, context: &mut VMContextBuilder, amount: Balance, guarantee: bool) {
+        testing_env!(context.predecessor_account_id(ft()).build());
+        let msg = format!(r#"{{"SPONSOR":{{"tournament_id":"1","guarantee":{}}}}}"#, guarantee);
+        contract.ft_on_transfer(accounts(5), U128(amount), msg);
+    }
+
+    fn settle(contract: &mut Wordchain, context: &mut VMContextBuilder) {
+        for player in 1..5 {
+            play(contract, context, accounts(player), player as u8 * 10);
+        }
+        context.block_timestamp((DAY_TO_MS + 1) * 1_000_000);
+        for player in 1..5 {
+            reveal(contract, context, accounts(player), player as u8 * 10);
+        }
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((3 * DAY_TO_MS) * 1_000_000).build());
+        contract.distribute_rewards(U128(1));
+    }
+
+    #[test]
+    fn test_sponsored_pool_skips_commission() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+        sponsor(&mut contract, &mut context, 2000, false);
+        assert_eq!(contract.get_sponsorships(U128(1)).len(), 1);
+
+        settle(&mut contract, &mut context);
+
+        // Half of the 3400 left of the stakes plus the 2000 sponsored.
+        assert_eq!(contract.stake_payouts.get(&ft()), Some(600));
+        assert_eq!(contract.get_player_profile(accounts(4)).winnings(&ft()), 2700);
+    }
+
+    #[test]
+    fn test_guarantee_tops_up_pool() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_active_tournament(&mut context);
+        sponsor(&mut contract, &mut context, 5000, true);
+
+        settle(&mut contract, &mut context);
+        assert_eq!(contract.get_player_profile(accounts(4)).winnings(&ft()), 2500);
+    }
+
+    #[test]
+    fn test_cancelled_tournament_refunds_sponsors() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup::default());
+        join(&mut contract, &mut context, accounts(1));
+        sponsor(&mut contract, &mut context, 2000, false);
+
+        testing_env!(context.predecessor_account_id(accounts(5)).block_timestamp(DAY_TO_MS * 1_000_000).build());
+        assert!(contract.advance_tournament(U128(1)) == TournamentState::EXPIRED);
+        assert!(contract.get_sponsorships(U128(1)).is_empty());
+    }
+
+    #[test]
+    fn test_failed_sponsor_refund_credited_as_stake() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        create_tournament(&mut contract, &mut context, TournamentSetup::default());
+        sponsor(&mut contract, &mut context, 2000, false);
+        testing_env!(context.predecessor_account_id(accounts(5)).block_timestamp(DAY_TO_MS * 1_000_000).build());
+        contract.advance_tournament(U128(1));
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.on_transfer_resolved(ft(), accounts(5), U128(2000), TransferFallback::STAKE, Err(PromiseError::Failed));
+        assert_eq!(contract.stakes.get(&accounts(5)).unwrap().get(&ft()), Some(2000));
+    }
+}
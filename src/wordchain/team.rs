@@ -0,0 +1,326 @@
+use super::*;
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Eq, PartialEq)]
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TeamAggregation {
+    SUM,
+    AVERAGE,
+}
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Eq, PartialEq)]
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TeamPrizeSplit {
+    STAKE,
+    EQUAL,
+}
+
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TeamSettings {
+    aggregation: TeamAggregation,
+    /// Number of best member scores counted, 0 counts every member.
+    top_k: u32,
+    prize_split: TeamPrizeSplit,
+    max_team_size: u32,
+}
+
+impl TeamSettings {
+    pub(crate) fn assert_valid(&self) {
+        require!(self.max_team_size > 1, "Teams must allow at least two members");
+    }
+
+    pub(crate) fn splits_by_stake(&self) -> bool {
+        self.prize_split == TeamPrizeSplit::STAKE
+    }
+}
+
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Team {
+    id: u32,
+    name: String,
+    captain: AccountId,
+    members: Vec<AccountId>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TeamStanding {
+    team_id: u32,
+    name: String,
+    score: u64,
+    members: u32,
+}
+
+impl Tournament {
+    fn team_of(&self, account_id: &AccountId) -> Option<&Team> {
+        self.teams.iter().find(|team| team.members.contains(account_id))
+    }
+
+    fn team(&self, team_id: u32) -> Option<&Team> {
+        self.teams.iter().find(|team| team.id == team_id)
+    }
+
+    /// Team tournaments pay prizes to teams only, so a player must be on one before playing.
+    pub(crate) fn assert_can_play_for_team(&self, account_id: &AccountId) {
+        if self.team_settings.is_some() {
+            require!(self.team_of(account_id).is_some(), "Join a team before playing");
+        }
+    }
+
+    /// Drops a leaving player from their team, handing the captaincy to the next member or deleting the team once empty.
+    pub(crate) fn remove_from_team(&mut self, account_id: &AccountId) {
+        if let Some(team) = self.teams.iter_mut().find(|team| team.members.contains(account_id)) {
+            team.members.retain(|member| member != account_id);
+            if &team.captain == account_id {
+                if let Some(captain) = team.members.first() {
+                    team.captain = captain.clone();
+                }
+            }
+        }
+        self.teams.retain(|team| !team.members.is_empty());
+    }
+
+    fn team_score(&self, team: &Team, settings: &TeamSettings) -> u64 {
+        let final_round = self.final_round();
+        let mut scores = self.players.iter()
+            .filter(|player| team.members.contains(&player.account_id))
+            .map(|player| player.round_score(final_round).average_score())
+            .collect::<Vec<u64>>();
+
+        scores.sort_unstable_by(|a, b| b.cmp(a));
+        if settings.top_k > 0 {
+            scores.truncate(settings.top_k as usize);
+        }
+
+        let sum = scores.iter().sum::<u64>();
+        match settings.aggregation {
+            TeamAggregation::SUM => sum,
+            TeamAggregation::AVERAGE => sum.checked_div(scores.len() as u64).unwrap_or_default(),
+        }
+    }
+
+    pub(crate) fn team_standings(&self) -> Vec<TeamStanding> {
+        let settings = match &self.team_settings {
+            Some(settings) => settings,
+            None => return Vec::new(),
+        };
+
+        let mut standings = self.teams.iter().map(|team| TeamStanding {
+            team_id: team.id,
+            name: team.name.clone(),
+            score: self.team_score(team, settings),
+            members: team.members.len() as u32,
+        }).collect::<Vec<TeamStanding>>();
+
+        standings.sort_by_key(|standing| std::cmp::Reverse(standing.score));
+        standings
+    }
+
+    /// Team members grouped by team placement, weighted by stake or equally as configured.
+    pub(crate) fn team_placements(&self) -> Vec<Vec<(AccountId, Balance)>> {
+        let split = self.team_settings.as_ref().map(|settings| settings.prize_split.clone());
+
+        self.team_standings().into_iter().map(|standing| {
+            let team = self.team(standing.team_id).unwrap();
+            team.members.iter().map(|account_id| {
+                let weight = match split {
+                    Some(TeamPrizeSplit::STAKE) => self.players.iter()
+                        .find(|player| &player.account_id == account_id)
+                        .map(|player| player.stake_amount)
+                        .unwrap_or_default(),
+                    _ => 1,
+                };
+                (account_id.clone(), weight)
+            }).collect()
+        }).collect()
+    }
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    /// Creates a team captained by the caller, who must already have joined the tournament.
+    pub fn create_team(&mut self, tournament_id: U128, name: String) -> Team {
+        let mut tournament = self.internal_get_tournament(tournament_id);
+        self.internal_assert_can_join_team(&tournament);
+        require!(!tournament.teams.iter().any(|team| team.name == name), "Team with provided name already exists");
+
+        // Empty teams are deleted, so teams are looked up by id rather than by position.
+        let team = Team {
+            id: tournament.teams.iter().map(|team| team.id + 1).max().unwrap_or_default(),
+            name,
+            captain: env::predecessor_account_id(),
+            members: vec![env::predecessor_account_id()],
+        };
+        tournament.teams.push(team.clone());
+        self.internal_save_tournament(&tournament);
+
+        team
+    }
+
+    pub fn join_team(&mut self, tournament_id: U128, team_id: u32) {
+        let mut tournament = self.internal_get_tournament(tournament_id);
+        self.internal_assert_can_join_team(&tournament);
+
+        let max_team_size = tournament.team_settings.as_ref().unwrap().max_team_size;
+        let team = tournament.teams.iter_mut().find(|team| team.id == team_id);
+        require!(team.is_some(), "Team with provided ID does not exist");
+
+        let team = team.unwrap();
+        require!((team.members.len() as u32) < max_team_size, "Team is full");
+        team.members.push(env::predecessor_account_id());
+        self.internal_save_tournament(&tournament);
+    }
+
+    pub fn get_team_roster(&self, tournament_id: U128, team_id: u32) -> Team {
+        self.internal_get_tournament(tournament_id).team(team_id).cloned()
+            .unwrap_or_else(|| env::panic_str("Team with provided ID does not exist"))
+    }
+
+    pub fn get_team_standings(&self, tournament_id: U128) -> Vec<TeamStanding> {
+        self.internal_get_tournament(tournament_id).team_standings()
+    }
+}
+
+impl Wordchain {
+
+    fn internal_assert_can_join_team(&self, tournament: &Tournament) {
+        require!(tournament.team_settings.is_some(), "Tournament is not a team tournament");
+        require!(
+            tournament.status == TournamentState::PENDING_VOLUME || tournament.status == TournamentState::ACTIVE,
+            "Teams can not be changed at this time"
+        );
+        require!(
+            tournament.players.iter().any(|player| player.account_id == env::predecessor_account_id()),
+            "Join the tournament before joining a team"
+        );
+        require!(tournament.team_of(&env::predecessor_account_id()).is_none(), "Player already belongs to a team");
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{ft, get_context, play, reveal, setup_tournament, stake, TournamentSetup};
+
+    fn settings(aggregation: TeamAggregation, top_k: u32, prize_split: TeamPrizeSplit) -> TeamSettings {
+        TeamSettings { aggregation, top_k, prize_split, max_team_size: 2 }
+    }
+
+    fn form_teams(contract: &mut Wordchain, context: &mut near_sdk::test_utils::VMContextBuilder) {
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.create_team(U128(1), "red".to_string());
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.join_team(U128(1), 0);
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.create_team(U128(1), "blue".to_string());
+        testing_env!(context.predecessor_account_id(accounts(4)).build());
+        contract.join_team(U128(1), 1);
+    }
+
+    #[test]
+    fn test_team_standings_and_placements() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_tournament(&mut context, TournamentSetup { team_settings: Some(settings(TeamAggregation::SUM, 1, TeamPrizeSplit::EQUAL)), ..Default::default() });
+        form_teams(&mut contract, &mut context);
+
+        for (player, score) in [(1, 10), (2, 20), (3, 30), (4, 5)] {
+            play(&mut contract, &mut context, accounts(player), score);
+        }
+        context.block_timestamp((DAY_TO_MS + 1) * 1_000_000);
+        for (player, score) in [(1, 10), (2, 20), (3, 30), (4, 5)] {
+            reveal(&mut contract, &mut context, accounts(player), score);
+        }
+
+        let standings = contract.get_team_standings(U128(1));
+        assert_eq!(standings[0].name, "blue");
+        assert_eq!(standings[0].score, 30_000);
+        assert_eq!(standings[1].score, 20_000);
+
+        let placements = contract.internal_get_tournament(U128(1)).team_placements();
+        assert_eq!(placements[0], vec![(accounts(3), 1), (accounts(4), 1)]);
+        assert_eq!(contract.get_team_roster(U128(1), 0).captain, accounts(1));
+    }
+
+    #[test]
+    fn test_unpaid_placements_credited_to_treasury() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_tournament(&mut context, TournamentSetup { team_settings: Some(settings(TeamAggregation::SUM, 1, TeamPrizeSplit::EQUAL)), ..Default::default() });
+        form_teams(&mut contract, &mut context);
+
+        for (player, score) in [(1, 10), (2, 20), (3, 30), (4, 5)] {
+            play(&mut contract, &mut context, accounts(player), score);
+        }
+        context.block_timestamp((DAY_TO_MS + 1) * 1_000_000);
+        for (player, score) in [(1, 10), (2, 20), (3, 30), (4, 5)] {
+            reveal(&mut contract, &mut context, accounts(player), score);
+        }
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((3 * DAY_TO_MS) * 1_000_000).build());
+        contract.distribute_rewards(U128(1));
+
+        // Every player is on one of the two teams, which take 50% and 34% of the 3400 prize pool; the third prize has no team to go to.
+        assert_eq!(contract.stake_payouts.get(&ft()), Some(600 + 544));
+
+        // Five stakes of 1000 were deposited and 1700 + 1156 paid out as prizes.
+        let report = near_sdk::serde_json::to_value(contract.on_reconcile(ft(), Ok(U128(5000 - 1700 - 1156)))).unwrap();
+        assert_eq!(report["surplus"], "0");
+        assert_eq!(report["deficit"], "0");
+    }
+
+    #[test]
+    #[should_panic(expected = "Team is full")]
+    fn test_team_size_is_capped() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_tournament(&mut context, TournamentSetup { team_settings: Some(settings(TeamAggregation::AVERAGE, 0, TeamPrizeSplit::STAKE)), ..Default::default() });
+        form_teams(&mut contract, &mut context);
+
+        stake(&mut contract, &mut context, accounts(5));
+        testing_env!(context.predecessor_account_id(accounts(5)).build());
+        contract.join_tournament(U128(1), "NG".to_string(), None, None);
+        contract.join_team(U128(1), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Join a team before playing")]
+    fn test_teamless_player_can_not_play() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_tournament(&mut context, TournamentSetup { team_settings: Some(settings(TeamAggregation::SUM, 1, TeamPrizeSplit::EQUAL)), ..Default::default() });
+
+        play(&mut contract, &mut context, accounts(1), 10);
+    }
+
+    #[test]
+    fn test_empty_team_deleted() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_tournament(&mut context, TournamentSetup {
+            team_settings: Some(settings(TeamAggregation::SUM, 1, TeamPrizeSplit::EQUAL)),
+            min_players: Some(5),
+            ..Default::default()
+        });
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.create_team(U128(1), "red".to_string());
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.create_team(U128(1), "blue".to_string());
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.leave_tournament(U128(1));
+
+        let tournament = contract.internal_get_tournament(U128(1));
+        assert_eq!(tournament.teams.len(), 1);
+        assert_eq!(contract.get_team_roster(U128(1), 1).name, "blue");
+
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        assert_eq!(contract.create_team(U128(1), "green".to_string()).id, 2);
+        testing_env!(context.predecessor_account_id(accounts(4)).build());
+        contract.join_team(U128(1), 1);
+        assert_eq!(contract.get_team_roster(U128(1), 1).members, vec![accounts(2), accounts(4)]);
+    }
+}
@@ -0,0 +1,429 @@
+use super::*;
+use super::schedule::MIN_ROUND_DURATION_MS;
+
+/// Keys of spawned instances are `"template-{template id}-{occurrence}"` and can not be used by other tournaments.
+pub const TEMPLATE_KEY_PREFIX: &str = "template-";
+
+/// Whether `name` is one a template called `template_name` gives its instances, `"{template name} #{occurrence}"`.
+fn is_instance_name(template_name: &str, name: &str) -> bool {
+    name.strip_prefix(template_name)
+        .and_then(|rest| rest.strip_prefix(" #"))
+        .is_some_and(|occurrence| !occurrence.is_empty() && occurrence.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Everything a tournament is created from apart from its name, key and schedule.
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TournamentConfig {
+    pub(crate) game_type_id: String,
+    pub(crate) form: TournamentType,
+    pub(crate) minimum_stake: U128,
+    pub(crate) ft_address: AccountId,
+    pub(crate) country: Option<String>,
+    pub(crate) round_duration: u64,
+    pub(crate) rounds: u32,
+    pub(crate) eliminations_per_round: u32,
+    pub(crate) format: TournamentFormat,
+    pub(crate) bracket_seeding: BracketSeeding,
+    pub(crate) team_settings: Option<TeamSettings>,
+    pub(crate) eligibility_rules: Vec<EligibilityRule>,
+    pub(crate) min_players: u32,
+    pub(crate) max_players: u32,
+    pub(crate) prize_curve: Vec<u64>,
+    pub(crate) creator_fee: u64,
+}
+
+/// The optional settings of `create_tournament`; anything left out takes the platform default.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde", default)]
+pub struct TournamentSettings {
+    /// PUBLIC unless set.
+    pub form: Option<TournamentType>,
+    pub country: Option<String>,
+    /// Length of each round in days, one day unless set.
+    pub interval: Option<u64>,
+    pub rounds: Option<u32>,
+    pub eliminations_per_round: Option<u32>,
+    pub format: Option<TournamentFormat>,
+    /// RANDOM unless set; only read by bracket tournaments.
+    pub bracket_seeding: Option<BracketSeeding>,
+    pub team_settings: Option<TeamSettings>,
+    pub eligibility_rules: Option<Vec<EligibilityRule>>,
+    pub min_players: Option<u32>,
+    pub max_players: Option<u32>,
+    pub schedule: Option<ScheduleInput>,
+    pub prize_curve: Option<Vec<u64>>,
+    pub creator_fee: Option<u64>,
+}
+
+impl TournamentSettings {
+    pub(crate) fn into_config(self, contract: &Wordchain, game_type_id: String, ft_address: AccountId, minimum_stake: U128) -> TournamentConfig {
+        let round_duration = self.interval.unwrap_or(1).checked_mul(DAY_TO_MS);
+        require!(round_duration.is_some(), "Round interval is too long");
+
+        TournamentConfig {
+            game_type_id,
+            form: self.form.unwrap_or(TournamentType::PUBLIC),
+            minimum_stake,
+            ft_address,
+            country: self.country,
+            round_duration: round_duration.unwrap(),
+            rounds: self.rounds.unwrap_or(1),
+            eliminations_per_round: self.eliminations_per_round.unwrap_or_default(),
+            format: self.format.unwrap_or(TournamentFormat::LEADERBOARD),
+            bracket_seeding: self.bracket_seeding.unwrap_or(BracketSeeding::RANDOM),
+            team_settings: self.team_settings,
+            eligibility_rules: self.eligibility_rules.unwrap_or_default(),
+            min_players: self.min_players.unwrap_or(contract.min_tournament_players as u32),
+            max_players: self.max_players.unwrap_or(contract.max_tournament_players),
+            prize_curve: self.prize_curve.unwrap_or_else(|| DEFAULT_PRIZE_CURVE.to_vec()),
+            creator_fee: self.creator_fee.unwrap_or_default(),
+        }
+    }
+}
+
+impl TournamentConfig {
+    pub(crate) fn assert_valid(&self, contract: &Wordchain) {
+        require!(self.rounds > 0, "Tournament must have at least one round");
+        require!(self.round_duration >= MIN_ROUND_DURATION_MS, "Rounds must last at least one hour");
+        if self.format == TournamentFormat::BRACKET {
+            require!(self.eliminations_per_round == 0, "Bracket tournaments eliminate through their matches");
+            require!(self.team_settings.is_none(), "Bracket tournaments are played individually");
+        }
+        if let Some(settings) = &self.team_settings {
+            settings.assert_valid();
+            require!(self.minimum_stake.0 > 0 || !settings.splits_by_stake(), "Free tournaments can not split team prizes by stake");
+        }
+
+        require!(self.min_players >= contract.min_tournament_players as u32, "Minimum number of players is below the platform minimum");
+        require!(self.max_players <= contract.max_tournament_players, "Maximum number of players is above the platform maximum");
+        require!(self.min_players <= self.max_players, "Minimum number of players can not exceed the maximum");
+
+        require!(!self.prize_curve.is_empty(), "Prize curve must pay at least one placement");
+        require!(self.prize_curve.iter().sum::<u64>() == 10000, "Prize curve must add up to 10000 basis points");
+        require!(self.prize_curve.len() <= self.min_players as usize, "Prize curve pays more placements than the minimum number of players");
+        require!(self.creator_fee <= contract.max_creator_fee, "Creator fee is above the platform maximum");
+        require!(
+            contract.wct_token.is_some() || !self.eligibility_rules.iter().any(|rule| matches!(rule, EligibilityRule::MIN_WCT_DEPOSIT(_))),
+            "WCT deposit rule requires a WCT token to be configured"
+        );
+
+        if self.form == TournamentType::COUNTRY_BASED {
+            require!(self.country.is_some(), "Country based tournament requires a country to be passed");
+            require!(contract.check_supported_country(self.country.clone().unwrap_or_default()), "Country code entered is not supported");
+        }
+        require!(!contract.get_gametypes(Some(self.game_type_id.clone())).is_empty(), "No tournament with provided game type");
+    }
+}
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Recurrence {
+    DAILY,
+    WEEKLY,
+    EVERY_MS(u64),
+}
+
+impl Recurrence {
+    fn period(&self) -> u64 {
+        match self {
+            Recurrence::DAILY => DAY_TO_MS,
+            Recurrence::WEEKLY => 7 * DAY_TO_MS,
+            Recurrence::EVERY_MS(period) => *period,
+        }
+    }
+}
+
+/// An owner defined tournament spawned again every `recurrence` period, starting at `first_start`.
+/// Registration for each instance opens `registration_lead` ms before its play starts.
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TournamentTemplate {
+    id: u64,
+    name: String,
+    config: TournamentConfig,
+    recurrence: Recurrence,
+    first_start: u64,
+    registration_lead: u64,
+    max_occurrences: Option<u32>,
+    next_occurrence: u32,
+    active: bool,
+}
+
+impl TournamentTemplate {
+    fn slot_start(&self, occurrence: u32) -> u64 {
+        self.first_start + occurrence as u64 * self.recurrence.period()
+    }
+
+    /// The latest occurrence whose registration has opened, if it has not been spawned yet.
+    /// Occurrences missed entirely are skipped rather than spawned late.
+    fn due_occurrence(&self, timestamp: u64) -> Option<u32> {
+        let opened_since = timestamp.checked_add(self.registration_lead)?.checked_sub(self.first_start)?;
+        let occurrence = (opened_since / self.recurrence.period()) as u32;
+
+        let within_limit = self.max_occurrences.is_none_or(|max| occurrence < max);
+        (occurrence >= self.next_occurrence && within_limit).then_some(occurrence)
+    }
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    /// Registers a recurring tournament owned by the contract owner; instances are spawned by `spawn_from_template`.
+    pub fn create_tournament_template(
+        &mut self,
+        name: String,
+        config: TournamentConfig,
+        recurrence: Recurrence,
+        first_start: TimeInput,
+        registration_lead: Option<u64>,
+        max_occurrences: Option<u32>,
+    ) -> u64 {
+        require!(env::predecessor_account_id() == self.owner, "Only the owner can create tournament templates");
+        require!(config.form != TournamentType::PRIVATE, "Private tournaments can not be recurring");
+        require!(!self.templates.iter().any(|template| template.name == name), "Tournament template with provided name already exists");
+        require!(
+            !self.tournaments.iter().any(|tournament| is_instance_name(&name, &tournament.name)),
+            "Tournament template name clashes with an existing tournament"
+        );
+        require!(recurrence.period() > 0, "Recurrence period must be greater than 0");
+        require!(recurrence.period() >= config.round_duration, "Recurrence must not be shorter than a round");
+        config.assert_valid(self);
+
+        let template = TournamentTemplate {
+            id: self.templates.len() + 1,
+            name,
+            config,
+            recurrence,
+            first_start: first_start.to_ms(),
+            registration_lead: registration_lead.unwrap_or_default(),
+            max_occurrences,
+            next_occurrence: 0,
+            active: true,
+        };
+        self.templates.push(&template);
+        template.id
+    }
+
+    pub fn set_template_active(&mut self, template_id: u64, active: bool) {
+        require!(env::predecessor_account_id() == self.owner, "Only the owner can update tournament templates");
+        let mut template = self.internal_get_template(template_id);
+        template.active = active;
+        self.templates.replace(template_id - 1, &template);
+        WordchainEvent::config_changed("template_active", format!("{}:{}", template_id, active));
+    }
+
+    /// Creates the next instance of a template once its registration slot arrives, callable by anyone.
+    /// Instances are named `"{template name} #{occurrence}"`, numbered from 1.
+    pub fn spawn_from_template(&mut self, template_id: u64) -> Tournament {
+        require!(!self.paused, "Contract is paused");
+        let mut template = self.internal_get_template(template_id);
+        require!(template.active, "Tournament template is not active");
+
+        let occurrence = template.due_occurrence(env::block_timestamp_ms());
+        require!(occurrence.is_some(), "Tournament template has no instance due");
+        let occurrence = occurrence.unwrap();
+
+        template.config.assert_valid(self);
+        // An instance spawned after its slot has started plays from now rather than from the past.
+        let play_start = template.slot_start(occurrence).max(env::block_timestamp_ms());
+        let schedule = ScheduleInput::starting_at(play_start.saturating_sub(template.registration_lead), play_start);
+
+        template.next_occurrence = occurrence + 1;
+        self.templates.replace(template_id - 1, &template);
+
+        self.internal_create_tournament(
+            format!("{} #{}", template.name, occurrence + 1),
+            format!("{}{}-{}", TEMPLATE_KEY_PREFIX, template.id, occurrence + 1),
+            self.owner.clone(),
+            template.config,
+            schedule,
+            Vec::new(),
+        )
+    }
+
+    pub fn get_template(&self, template_id: u64) -> TournamentTemplate {
+        self.internal_get_template(template_id)
+    }
+
+    pub fn get_templates(&self) -> Vec<TournamentTemplate> {
+        self.templates.iter().collect()
+    }
+}
+
+impl Wordchain {
+
+    /// Keeps other tournaments out of the names and keys template instances are given, so every spawn is unique.
+    pub(crate) fn internal_assert_not_template_instance(&self, name: &str, tournament_key: &str) {
+        require!(!tournament_key.starts_with(TEMPLATE_KEY_PREFIX), "Tournament keys starting with template- are reserved");
+        require!(
+            !self.templates.iter().any(|template| is_instance_name(&template.name, name)),
+            "Tournament name is reserved for a tournament template"
+        );
+    }
+
+    fn internal_get_template(&self, template_id: u64) -> TournamentTemplate {
+        let template = template_id.checked_sub(1).and_then(|index| self.templates.get(index));
+        require!(template.is_some(), "Tournament template with provided ID does not exist");
+        template.unwrap()
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{ft, get_context, new_contract};
+
+    fn daily_config() -> TournamentConfig {
+        TournamentConfig {
+            game_type_id: "chain".to_string(),
+            form: TournamentType::PUBLIC,
+            minimum_stake: U128(1000),
+            ft_address: ft(),
+            country: None,
+            round_duration: DAY_TO_MS / 2,
+            rounds: 1,
+            eliminations_per_round: 0,
+            format: TournamentFormat::LEADERBOARD,
+            bracket_seeding: BracketSeeding::RANDOM,
+            team_settings: None,
+            eligibility_rules: Vec::new(),
+            min_players: 4,
+            max_players: 100,
+            prize_curve: vec![7000, 3000],
+            creator_fee: 0,
+        }
+    }
+
+    #[test]
+    fn test_spawn_from_template() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        let template_id = contract.create_tournament_template(
+            "Daily Blitz".to_string(),
+            daily_config(),
+            Recurrence::DAILY,
+            TimeInput::MS(DAY_TO_MS),
+            Some(DAY_TO_MS / 4),
+            None,
+        );
+
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp((DAY_TO_MS - DAY_TO_MS / 4) * 1_000_000).build());
+        let first = contract.spawn_from_template(template_id);
+        assert_eq!(first.name, "Daily Blitz #1");
+        assert_eq!(first.rounds[0].start, DAY_TO_MS);
+        assert_eq!(first.prize_curve, vec![7000, 3000]);
+
+        // The second day is skipped, so the next spawn is the third instance.
+        testing_env!(context.block_timestamp((3 * DAY_TO_MS) * 1_000_000).build());
+        let third = contract.spawn_from_template(template_id);
+        assert_eq!(third.name, "Daily Blitz #3");
+        assert_eq!(third.id, U128(2));
+        assert_eq!(contract.get_template(template_id).next_occurrence, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tournament name is reserved for a tournament template")]
+    fn test_instance_names_reserved() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        contract.create_tournament_template(
+            "Daily Blitz".to_string(),
+            daily_config(),
+            Recurrence::DAILY,
+            TimeInput::MS(DAY_TO_MS),
+            None,
+            None,
+        );
+        assert!(is_instance_name("Daily Blitz", "Daily Blitz #5"));
+        assert!(!is_instance_name("Daily Blitz", "Daily Blitz #5b"));
+
+        contract.create_tournament("Daily Blitz #5".to_string(), "key".to_string(), "chain".to_string(), ft(), U128(1000), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Recurrence period must be greater than 0")]
+    fn test_template_rejects_zero_period() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        contract.create_tournament_template(
+            "Blitz".to_string(),
+            TournamentConfig { round_duration: 0, ..daily_config() },
+            Recurrence::EVERY_MS(0),
+            TimeInput::MS(DAY_TO_MS),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Prize curve pays more placements than the minimum number of players")]
+    fn test_template_bounds_prize_curve() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        contract.create_tournament_template(
+            "Daily Blitz".to_string(),
+            TournamentConfig { prize_curve: vec![2000; 5], ..daily_config() },
+            Recurrence::DAILY,
+            TimeInput::MS(DAY_TO_MS),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Tournament template has no instance due")]
+    fn test_spawn_before_slot() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        let template_id = contract.create_tournament_template(
+            "Daily Blitz".to_string(),
+            daily_config(),
+            Recurrence::DAILY,
+            TimeInput::MS(DAY_TO_MS),
+            None,
+            None,
+        );
+
+        testing_env!(context.block_timestamp(DAY_TO_MS * 1_000_000).build());
+        contract.spawn_from_template(template_id);
+        contract.spawn_from_template(template_id);
+    }
+
+    #[test]
+    fn test_template_deactivation_logged() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        let template_id = contract.create_tournament_template(
+            "Daily Blitz".to_string(),
+            daily_config(),
+            Recurrence::DAILY,
+            TimeInput::MS(DAY_TO_MS),
+            None,
+            None,
+        );
+
+        contract.set_template_active(template_id, false);
+        assert!(get_logs().last().unwrap().contains(r#""parameter":"template_active","value":"1:false""#));
+    }
+
+    #[test]
+    fn test_overflowing_registration_lead_is_never_due() {
+        let mut context = get_context(accounts(0));
+        let mut contract = new_contract(&mut context);
+        let template_id = contract.create_tournament_template(
+            "Daily Blitz".to_string(),
+            daily_config(),
+            Recurrence::DAILY,
+            TimeInput::MS(DAY_TO_MS),
+            Some(u64::MAX),
+            None,
+        );
+
+        assert!(contract.get_template(template_id).due_occurrence(DAY_TO_MS).is_none());
+    }
+}
@@ -0,0 +1,65 @@
+use super::*;
+
+pub const GAS_FOR_FT_TRANSFER: Gas = Gas(10_000_000_000_000);
+pub const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(10_000_000_000_000);
+
+/// Where the amount of a failed transfer is credited back to.
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TransferFallback {
+    CREATOR_EARNINGS,
+    REFERRAL_EARNINGS,
+    /// The receiver's stake balance, from which they can enter other tournaments.
+    STAKE,
+    /// The treasury the keeper bounty was paid from.
+    KEEPER_BOUNTY,
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    /// Credits the amount of a failed transfer back to where it was debited from, so the receiver can claim it again.
+    #[private]
+    pub fn on_transfer_resolved(
+        &mut self,
+        ft_address: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        fallback: TransferFallback,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) {
+        if result.is_ok() {
+            return;
+        }
+        match fallback {
+            TransferFallback::CREATOR_EARNINGS => self.internal_credit_creator(&receiver_id, &ft_address, amount.0),
+            TransferFallback::REFERRAL_EARNINGS => self.internal_credit_referrer(&receiver_id, &ft_address, amount.0),
+            TransferFallback::STAKE => self.internal_credit_stake(&receiver_id, &ft_address, amount.0),
+            TransferFallback::KEEPER_BOUNTY => self.internal_restore_keeper_bounty(&ft_address, amount.0),
+        }
+        WordchainEvent::TransferFailed { receiver_id, ft_address, amount }.emit();
+    }
+}
+
+impl Wordchain {
+
+    /// A NEP-141 `ft_transfer` of `amount` to `receiver_id`, with the 1 yoctoNEAR the standard requires attached.
+    /// Callers chain a callback onto it to restore what they debited if the transfer fails.
+    pub(crate) fn internal_ft_transfer(ft_address: &AccountId, receiver_id: &AccountId, amount: Balance) -> Promise {
+        ext_token_contract::ext(ft_address.clone())
+            .with_attached_deposit(1)
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .ft_transfer(receiver_id.clone(), U128(amount), None)
+    }
+
+    /// Transfers `amount` to `receiver_id`, crediting it back to them through `fallback` if the transfer fails.
+    pub(crate) fn internal_ft_transfer_or_restore(ft_address: &AccountId, receiver_id: &AccountId, amount: Balance, fallback: TransferFallback) -> Promise {
+        Self::internal_ft_transfer(ft_address, receiver_id, amount)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .on_transfer_resolved(ft_address.clone(), receiver_id.clone(), U128(amount), fallback)
+            )
+    }
+}
@@ -0,0 +1,315 @@
+use super::*;
+
+/// Commission a token has earned over time and what left the treasury; what is still held lives in `stake_payouts`.
+#[derive(Clone, Default, BorshDeserialize, BorshSerialize)]
+pub struct TreasuryTotals {
+    earned: Balance,
+    keeper_bounties: Balance,
+    withdrawn: Balance,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TreasuryReport {
+    earned: U128,
+    keeper_bounties: U128,
+    withdrawn: U128,
+    pending: U128,
+}
+
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Withdrawal {
+    id: u64,
+    ft_address: AccountId,
+    to: AccountId,
+    amount: U128,
+    timestamp: u64,
+    /// Set when the token transfer failed and the amount went back to the treasury.
+    reverted: bool,
+}
+
+/// At most `max_amount` can be withdrawn in each `period_ms` window, windows being aligned to multiples of the period.
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WithdrawalLimit {
+    period_ms: u64,
+    max_amount: U128,
+    period_start: u64,
+    used: U128,
+}
+
+impl WithdrawalLimit {
+    fn consume(&mut self, amount: Balance, timestamp: u64) {
+        let period_start = timestamp - timestamp % self.period_ms;
+        if period_start != self.period_start {
+            self.period_start = period_start;
+            self.used = U128(0);
+        }
+        require!(self.used.0 + amount <= self.max_amount.0, "Withdrawal exceeds the limit for this period");
+        self.used.0 += amount;
+    }
+
+    /// Gives back what a withdrawal at `timestamp` used, if its period is still the current one.
+    fn restore(&mut self, amount: Balance, timestamp: u64) {
+        if timestamp - timestamp % self.period_ms == self.period_start {
+            self.used.0 = self.used.0.saturating_sub(amount);
+        }
+    }
+}
+
+#[near_bindgen]
+impl Wordchain {
+
+    /// Withdraws `amount`, or everything pending, of a token's commission and returns what is left.
+    pub fn withdraw_value(&mut self, to: AccountId, ft_address: AccountId, amount: Option<U128>) -> Balance {
+        self.internal_assert_owner_action();
+        self.internal_withdraw_value(to, ft_address, amount)
+    }
+
+    /// Puts a withdrawal whose token transfer failed back in the treasury.
+    #[private]
+    pub fn on_withdraw_resolved(&mut self, withdrawal_id: u64, #[callback_result] result: Result<(), PromiseError>) {
+        if result.is_ok() {
+            return;
+        }
+        let mut withdrawal = self.withdrawals.get(withdrawal_id).unwrap();
+        let (ft_address, amount) = (withdrawal.ft_address.clone(), withdrawal.amount.0);
+
+        let payout = self.stake_payouts.get(&ft_address).unwrap_or_default();
+        self.stake_payouts.insert(&ft_address, &(payout + amount));
+        let mut totals = self.treasury_totals.get(&ft_address).unwrap_or_default();
+        totals.withdrawn -= amount;
+        self.treasury_totals.insert(&ft_address, &totals);
+        if let Some(mut limit) = self.withdrawal_limits.get(&ft_address) {
+            limit.restore(amount, withdrawal.timestamp);
+            self.withdrawal_limits.insert(&ft_address, &limit);
+        }
+
+        withdrawal.reverted = true;
+        self.withdrawals.replace(withdrawal_id, &withdrawal);
+        WordchainEvent::TransferFailed { receiver_id: withdrawal.to, ft_address, amount: withdrawal.amount }.emit();
+    }
+
+    pub fn get_treasury(&self, ft_address: AccountId) -> TreasuryReport {
+        let totals = self.treasury_totals.get(&ft_address).unwrap_or_default();
+        TreasuryReport {
+            earned: U128(totals.earned),
+            keeper_bounties: U128(totals.keeper_bounties),
+            withdrawn: U128(totals.withdrawn),
+            pending: U128(self.stake_payouts.get(&ft_address).unwrap_or_default()),
+        }
+    }
+
+    pub fn get_withdrawals(&self, from_index: Option<u64>, limit: Option<u64>) -> Vec<Withdrawal> {
+        self.withdrawals.iter()
+            .skip(from_index.unwrap_or_default() as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+
+    pub fn get_withdrawal_limit(&self, ft_address: AccountId) -> Option<WithdrawalLimit> {
+        self.withdrawal_limits.get(&ft_address)
+    }
+
+    /// Caps withdrawals of a token per period; a `max_amount` of `None` removes the cap.
+    pub fn set_withdrawal_limit(&mut self, ft_address: AccountId, period_ms: u64, max_amount: Option<U128>) {
+        self.internal_assert_owner_action();
+        self.internal_set_withdrawal_limit(ft_address, period_ms, max_amount);
+    }
+
+    pub fn get_withdrawal_allowlist(&self) -> Vec<AccountId> {
+        self.withdrawal_allowlist.clone()
+    }
+
+    /// Restricts withdrawals to the listed destinations; an empty list allows any destination.
+    pub fn set_withdrawal_allowlist(&mut self, destinations: Vec<AccountId>) {
+        self.internal_assert_owner_action();
+        self.internal_set_withdrawal_allowlist(destinations);
+    }
+}
+
+impl Wordchain {
+
+    pub(crate) fn internal_set_withdrawal_limit(&mut self, ft_address: AccountId, period_ms: u64, max_amount: Option<U128>) {
+        match max_amount {
+            Some(max_amount) => {
+                require!(period_ms > 0, "Withdrawal period must be greater than 0");
+                self.withdrawal_limits.insert(&ft_address, &WithdrawalLimit { period_ms, max_amount, period_start: 0, used: U128(0) });
+            },
+            None => {
+                self.withdrawal_limits.remove(&ft_address);
+            },
+        }
+        WordchainEvent::config_changed("withdrawal_limit", format!("{}:{}:{}", ft_address, period_ms, max_amount.map_or(0, |amount| amount.0)));
+    }
+
+    pub(crate) fn internal_set_withdrawal_allowlist(&mut self, destinations: Vec<AccountId>) {
+        WordchainEvent::config_changed(
+            "withdrawal_allowlist",
+            destinations.iter().map(|account_id| account_id.to_string()).collect::<Vec<String>>().join("|"),
+        );
+        self.withdrawal_allowlist = destinations;
+    }
+
+    pub(crate) fn internal_withdraw_value(&mut self, to: AccountId, ft_address: AccountId, amount: Option<U128>) -> Balance {
+        require!(
+            self.withdrawal_allowlist.is_empty() || self.withdrawal_allowlist.contains(&to),
+            "Withdrawal destination is not on the allowlist"
+        );
+
+        let payout = self.stake_payouts.get(&ft_address).unwrap_or_default();
+        let amount = amount.map_or(payout, |amount| amount.0);
+        require!(amount > 0, "Nothing to withdraw");
+        require!(amount <= payout, "Confirm correct token address or withdraw amount below threshold");
+
+        if let Some(mut limit) = self.withdrawal_limits.get(&ft_address) {
+            limit.consume(amount, env::block_timestamp_ms());
+            self.withdrawal_limits.insert(&ft_address, &limit);
+        }
+
+        self.stake_payouts.insert(&ft_address, &(payout - amount));
+        let mut totals = self.treasury_totals.get(&ft_address).unwrap_or_default();
+        totals.withdrawn += amount;
+        self.treasury_totals.insert(&ft_address, &totals);
+
+        let withdrawal_id = self.withdrawals.len();
+        self.withdrawals.push(&Withdrawal {
+            id: withdrawal_id,
+            ft_address: ft_address.clone(),
+            to: to.clone(),
+            amount: U128(amount),
+            timestamp: env::block_timestamp_ms(),
+            reverted: false,
+        });
+        WordchainEvent::CommissionWithdrawn { to: to.clone(), ft_address: ft_address.clone(), amount: U128(amount) }.emit();
+
+        Self::internal_ft_transfer(&ft_address, &to, amount)
+            .then(Self::ext(env::current_account_id()).with_static_gas(GAS_FOR_RESOLVE_TRANSFER).on_withdraw_resolved(withdrawal_id));
+        payout - amount
+    }
+
+    pub(crate) fn internal_credit_treasury(&mut self, ft_address: &AccountId, amount: Balance) {
+        let stake_payout_for_ft = self.stake_payouts.get(ft_address).unwrap_or_default();
+        self.stake_payouts.insert(ft_address, &(stake_payout_for_ft + amount));
+
+        let mut totals = self.treasury_totals.get(ft_address).unwrap_or_default();
+        totals.earned += amount;
+        self.treasury_totals.insert(ft_address, &totals);
+    }
+
+    pub(crate) fn internal_debit_keeper_bounty(&mut self, ft_address: &AccountId, bounty: Balance) {
+        let stake_payout_for_ft = self.stake_payouts.get(ft_address).unwrap_or_default();
+        self.stake_payouts.insert(ft_address, &(stake_payout_for_ft - bounty));
+
+        let mut totals = self.treasury_totals.get(ft_address).unwrap_or_default();
+        totals.keeper_bounties += bounty;
+        self.treasury_totals.insert(ft_address, &totals);
+    }
+
+    pub(crate) fn internal_restore_keeper_bounty(&mut self, ft_address: &AccountId, bounty: Balance) {
+        let stake_payout_for_ft = self.stake_payouts.get(ft_address).unwrap_or_default();
+        self.stake_payouts.insert(ft_address, &(stake_payout_for_ft + bounty));
+
+        let mut totals = self.treasury_totals.get(ft_address).unwrap_or_default();
+        totals.keeper_bounties -= bounty;
+        self.treasury_totals.insert(ft_address, &totals);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_created_receipts};
+    use near_sdk::mock::VmAction;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::wordchain::tests::{ft, get_context, setup_active_tournament};
+
+    fn setup_settled_tournament(context: &mut near_sdk::test_utils::VMContextBuilder) -> Wordchain {
+        let mut contract = setup_active_tournament(context);
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((3 * DAY_TO_MS) * 1_000_000).build());
+        contract.distribute_rewards(U128(1));
+        contract
+    }
+
+    #[test]
+    fn test_withdrawals_debit_treasury() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_settled_tournament(&mut context);
+
+        assert_eq!(contract.withdraw_value(accounts(5), ft(), Some(U128(200))), 400);
+        assert_eq!(contract.withdraw_value(accounts(5), ft(), None), 0);
+
+        let totals = contract.get_treasury(ft());
+        assert_eq!(totals.earned, U128(600));
+        assert_eq!(totals.withdrawn, U128(600));
+        assert_eq!(totals.pending, U128(0));
+        assert_eq!(contract.get_withdrawals(None, None).len(), 2);
+    }
+
+    #[test]
+    fn test_withdrawal_sends_nep141_transfer() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_settled_tournament(&mut context);
+        contract.withdraw_value(accounts(5), ft(), Some(U128(200)));
+
+        let receipt = get_created_receipts().into_iter().rev().find(|receipt| receipt.receiver_id == ft()).unwrap();
+        match &receipt.actions[0] {
+            VmAction::FunctionCall { function_name, args, deposit, .. } => {
+                assert_eq!(function_name, "ft_transfer");
+                assert_eq!(deposit, &1);
+                assert_eq!(String::from_utf8(args.clone()).unwrap(), r#"{"receiver_id":"fargo","amount":"200","memo":null}"#);
+            },
+            _ => panic!("Expected a function call"),
+        }
+    }
+
+    #[test]
+    fn test_failed_withdrawal_restored() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_settled_tournament(&mut context);
+        contract.set_withdrawal_limit(ft(), DAY_TO_MS, Some(U128(250)));
+        contract.withdraw_value(accounts(5), ft(), Some(U128(200)));
+
+        contract.on_withdraw_resolved(0, Err(PromiseError::Failed));
+        let totals = contract.get_treasury(ft());
+        assert_eq!(totals.pending, U128(600));
+        assert_eq!(totals.withdrawn, U128(0));
+        assert!(contract.get_withdrawals(None, None)[0].reverted);
+        assert_eq!(contract.get_withdrawal_limit(ft()).unwrap().used, U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Confirm correct token address or withdraw amount below threshold")]
+    fn test_treasury_can_not_be_drained_twice() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_settled_tournament(&mut context);
+
+        contract.withdraw_value(accounts(5), ft(), Some(U128(600)));
+        contract.withdraw_value(accounts(5), ft(), Some(U128(600)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Withdrawal exceeds the limit for this period")]
+    fn test_withdrawal_limit_per_period() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_settled_tournament(&mut context);
+        contract.set_withdrawal_limit(ft(), DAY_TO_MS, Some(U128(250)));
+
+        contract.withdraw_value(accounts(5), ft(), Some(U128(250)));
+        testing_env!(context.block_timestamp((4 * DAY_TO_MS) * 1_000_000).build());
+        contract.withdraw_value(accounts(5), ft(), Some(U128(200)));
+        contract.withdraw_value(accounts(5), ft(), Some(U128(100)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Withdrawal destination is not on the allowlist")]
+    fn test_withdrawal_allowlist() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_settled_tournament(&mut context);
+        contract.set_withdrawal_allowlist(vec![accounts(5)]);
+
+        contract.withdraw_value(accounts(4), ft(), None);
+    }
+}
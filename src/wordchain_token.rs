@@ -6,10 +6,15 @@ use near_contract_standards::fungible_token::FungibleToken;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::LazyOption;
 use near_sdk::json_types::U128;
-use near_sdk::{env, log, near_bindgen, AccountId, Balance, PanicOnDefault, BorshStorageKey, PromiseOrValue, Promise, require};
+use crate::upgrade::{self, StagedCode};
+use near_sdk::{env, log, near_bindgen, AccountId, Balance, BorshStorageKey, PromiseOrValue, Promise, require};
 
 pub const TOTAL_SUPPLY: U128 = U128(100_000_000);
 
+/// Storage key holding the layout version of the token state; state written before versioning has none and is V1.
+pub const TOKEN_STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+pub const CURRENT_TOKEN_STATE_VERSION: u16 = 1;
+
 #[derive(BorshSerialize, BorshStorageKey)]
 enum StorageKey {
     FungibleToken,
@@ -31,6 +36,39 @@ impl Default for WordchainToken {
     }
 }
 
+/// Every layout the token state has been stored with. A new layout adds a variant and its conversion in `into_current`.
+enum VersionedWordchainToken {
+    V1(WordchainToken),
+}
+
+impl VersionedWordchainToken {
+    fn read() -> Self {
+        let version = env::storage_read(TOKEN_STATE_VERSION_KEY)
+            .map_or(1, |bytes| u16::try_from_slice(&bytes).unwrap_or_else(|_| env::panic_str("Invalid state version")));
+
+        match version {
+            1 => VersionedWordchainToken::V1(env::state_read().unwrap_or_else(|| env::panic_str("Contract is not initialized"))),
+            _ => env::panic_str("Unknown state version"),
+        }
+    }
+
+    fn write_version() {
+        env::storage_write(TOKEN_STATE_VERSION_KEY, &CURRENT_TOKEN_STATE_VERSION.try_to_vec().unwrap());
+    }
+
+    fn owner(&self) -> &AccountId {
+        match self {
+            VersionedWordchainToken::V1(state) => &state.owner,
+        }
+    }
+
+    fn into_current(self) -> WordchainToken {
+        match self {
+            VersionedWordchainToken::V1(state) => state,
+        }
+    }
+}
+
 
 
 // const DATA_IMAGE_SVG_NEAR_ICON: &str = "data:image/svg+xml,%3Csvg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 288 288'%3E%3Cg id='l' data-name='l'%3E%3Cpath d='M187.58,79.81l-30.1,44.69a3.2,3.2,0,0,0,4.75,4.2L191.86,103a1.2,1.2,0,0,1,2,.91v80.46a1.2,1.2,0,0,1-2.12.77L102.18,77.93A15.35,15.35,0,0,0,90.47,72.5H87.34A15.34,15.34,0,0,0,72,87.84V201.16A15.34,15.34,0,0,0,87.34,216.5h0a15.35,15.35,0,0,0,13.08-7.31l30.1-44.69a3.2,3.2,0,0,0-4.75-4.2L96.14,186a1.2,1.2,0,0,1-2-.91V104.61a1.2,1.2,0,0,1,2.12-.77l89.55,107.23a15.35,15.35,0,0,0,11.71,5.43h3.13A15.34,15.34,0,0,0,216,201.16V87.84A15.34,15.34,0,0,0,200.66,72.5h0A15.35,15.35,0,0,0,187.58,79.81Z'/%3E%3C/g%3E%3C/svg%3E";
@@ -57,8 +95,9 @@ impl WordchainToken {
             owner: owner_id.clone(),
             wordchain_contract: String::default()
         };
+        VersionedWordchainToken::write_version();
         this.token.internal_register_account(&owner_id);
-        this.token.internal_deposit(&owner_id, total_supply.clone().into());
+        this.token.internal_deposit(&owner_id, total_supply.into());
         near_contract_standards::fungible_token::events::FtMint {
             owner_id: &owner_id,
             amount: &total_supply,
@@ -68,6 +107,44 @@ impl WordchainToken {
         this
     }
 
+    /// Upgrades the stored state to the current layout after new code is deployed, callable by the owner or the contract itself.
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = VersionedWordchainToken::read();
+        require!(
+            state.owner() == &env::predecessor_account_id() || env::predecessor_account_id() == env::current_account_id(),
+            "Unauthorized"
+        );
+
+        VersionedWordchainToken::write_version();
+        state.into_current()
+    }
+
+    pub fn get_state_version(&self) -> u16 {
+        CURRENT_TOKEN_STATE_VERSION
+    }
+
+    /// Stages the wasm passed as the raw call input, deployable once `UPGRADE_TIMELOCK_MS` has passed.
+    pub fn stage_code(&mut self) -> StagedCode {
+        require!(env::predecessor_account_id() == self.owner, "Unauthorized");
+        upgrade::stage_code()
+    }
+
+    pub fn get_staged_code(&self) -> Option<StagedCode> {
+        upgrade::staged_code()
+    }
+
+    pub fn cancel_staged_code(&mut self) {
+        require!(env::predecessor_account_id() == self.owner, "Unauthorized");
+        upgrade::cancel_staged_code();
+    }
+
+    /// Deploys the staged code and migrates the state to it.
+    pub fn deploy_staged_code(&mut self) -> Promise {
+        require!(env::predecessor_account_id() == self.owner, "Unauthorized");
+        upgrade::deploy_staged_code()
+    }
+
     pub fn withdraw_near(&mut self, amount: U128) {
         require!(env::predecessor_account_id() == self.owner);
 
@@ -100,7 +177,6 @@ impl FungibleTokenMetadataProvider for WordchainToken {
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
     use near_sdk::test_utils::{accounts, VMContextBuilder};
-    use near_sdk::MockedBlockchain;
     use near_sdk::{testing_env, Balance};
 
     use super::*;
@@ -156,4 +232,43 @@ mod tests {
         assert_eq!(token.ft_balance_of(accounts(2)).0, (TOTAL_SUPPLY - transfer_amount));
         assert_eq!(token.ft_balance_of(accounts(1)).0, transfer_amount);
     }
+
+    #[test]
+    fn test_migrate_unversioned_state() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let token = WordchainToken::new(TOTAL_SUPPLY.into());
+        env::storage_remove(TOKEN_STATE_VERSION_KEY);
+        env::state_write(&token);
+
+        let migrated = WordchainToken::migrate();
+        assert_eq!(env::storage_read(TOKEN_STATE_VERSION_KEY), Some(CURRENT_TOKEN_STATE_VERSION.try_to_vec().unwrap()));
+        assert_eq!(migrated.owner, accounts(1));
+        assert_eq!(migrated.ft_balance_of(accounts(1)).0, TOTAL_SUPPLY);
+    }
+
+    #[test]
+    fn test_cancel_staged_code() {
+        let mut context = get_context(accounts(1));
+        context.context.input = b"\0asm".to_vec();
+        testing_env!(context.build());
+        let mut token = WordchainToken::new(TOTAL_SUPPLY.into());
+
+        token.stage_code();
+        assert!(token.get_staged_code().is_some());
+        token.cancel_staged_code();
+        assert!(token.get_staged_code().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn test_migrate_requires_owner() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let token = WordchainToken::new(TOTAL_SUPPLY.into());
+        env::state_write(&token);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        WordchainToken::migrate();
+    }
 }